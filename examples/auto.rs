@@ -37,6 +37,7 @@ struct MyApp<SS: SnakeSolver> {
 	speed: u32,
 	autoplay: bool,
 	overlay: bool,
+	occupancy: bool,
 }
 
 impl<SS: SnakeSolver> MyApp<SS> {
@@ -46,6 +47,7 @@ impl<SS: SnakeSolver> MyApp<SS> {
 			speed: 1,
 			autoplay: false,
 			overlay: true,
+			occupancy: false,
 		}
 	}
 }
@@ -54,8 +56,12 @@ impl<SS: SnakeSolver> eframe::App for MyApp<SS> {
 	fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
 		egui::CentralPanel::default().show(ctx, |ui| {
 			match self.world.state() {
-				AutoPlayerState::Playing | AutoPlayerState::Killed => {
-					let mut widget = SnakeWorldViewer::new(&self.world.world());
+				AutoPlayerState::Playing | AutoPlayerState::Killed | AutoPlayerState::Stalled => {
+					let mut widget = SnakeWorldViewer::new(self.world.world());
+
+					if self.occupancy {
+						widget = widget.with_occupancy_overlay(self.world.occupancy_forecast());
+					}
 
 					if self.overlay {
 						widget = widget.with_path_overlay(self.world.current_path());
@@ -74,6 +80,7 @@ impl<SS: SnakeSolver> eframe::App for MyApp<SS> {
 				ui.add(egui::Slider::new(&mut self.speed, 1..=10000));
 				ui.add(egui::Checkbox::new(&mut self.autoplay, "Autoplay"));
 				ui.add(egui::Checkbox::new(&mut self.overlay, "Overlay"));
+				ui.add(egui::Checkbox::new(&mut self.occupancy, "Occupancy"));
 			});
 
 			let mut steps = 0;
@@ -0,0 +1,140 @@
+use std::{
+	io::{self, Write},
+	time::Duration,
+};
+
+use crossterm::{
+	cursor,
+	event::{self, Event, KeyCode, KeyEvent},
+	execute, queue, terminal,
+};
+use snake_solver::{
+	auto::{AutoPlayerState, AutoSnakePlayer},
+	solvers::{
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+		SnakeSolver,
+	},
+	ui::{SnakeWorldViewer, TerminalColors},
+};
+
+const DEFAULT_GRID_SIZE: usize = 30;
+const FRAME_TIME: Duration = Duration::from_millis(33);
+
+fn main() -> io::Result<()> {
+	let grid_size = std::env::args()
+		.nth(1)
+		.map(|arg| arg.parse::<usize>().expect("World size must be a number"))
+		.unwrap_or(DEFAULT_GRID_SIZE);
+
+	let mut app = MyApp::new(
+		grid_size,
+		SnakeSpanningTreeSolver::new(JitterKind::JitterAlways(1)),
+	);
+
+	let mut stdout = io::stdout();
+	terminal::enable_raw_mode()?;
+	execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+	let result = app.run(&mut stdout);
+
+	execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+	terminal::disable_raw_mode()?;
+
+	result
+}
+
+struct MyApp<SS: SnakeSolver> {
+	world: AutoSnakePlayer<SS>,
+	speed: u32,
+	autoplay: bool,
+	overlay: bool,
+}
+
+impl<SS: SnakeSolver> MyApp<SS> {
+	fn new(grid_size: usize, solver: SS) -> MyApp<SS> {
+		Self {
+			world: AutoSnakePlayer::new(grid_size, solver),
+			speed: 1,
+			autoplay: false,
+			overlay: true,
+		}
+	}
+
+	fn run(&mut self, stdout: &mut impl Write) -> io::Result<()> {
+		loop {
+			self.draw(stdout)?;
+
+			let mut steps = 0;
+
+			if event::poll(FRAME_TIME)? {
+				if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+					match code {
+						KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+						KeyCode::Char('p') | KeyCode::Char(' ') => self.autoplay = !self.autoplay,
+						KeyCode::Char('o') => self.overlay = !self.overlay,
+						KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Up => {
+							self.speed = (self.speed * 2).min(10000)
+						}
+						KeyCode::Char('-') | KeyCode::Down => self.speed = (self.speed / 2).max(1),
+						KeyCode::Char('1') | KeyCode::Right => steps = self.speed as usize,
+						KeyCode::Char('2') => steps = self.speed as usize * 10,
+						KeyCode::Char('3') => steps = self.speed as usize * 100,
+						KeyCode::Char('4') => steps = self.speed as usize * 1000,
+						_ => {}
+					}
+				}
+			}
+
+			if self.autoplay {
+				steps = steps.max(self.speed as usize);
+			}
+
+			for _ in 0..steps {
+				self.world.step();
+			}
+		}
+	}
+
+	fn draw(&self, stdout: &mut impl Write) -> io::Result<()> {
+		let frame = match self.world.state() {
+			AutoPlayerState::Playing | AutoPlayerState::Killed | AutoPlayerState::Stalled => {
+				let mut widget = SnakeWorldViewer::new(self.world.world());
+
+				if self.overlay {
+					widget = widget.with_path_overlay(self.world.current_path());
+					widget = self.world.solver.decorate_widget(widget);
+				}
+
+				widget.render_terminal(TerminalColors::TrueColor)
+			}
+			AutoPlayerState::Finished => "Finished\n".to_string(),
+		};
+
+		queue!(
+			stdout,
+			cursor::MoveTo(0, 0),
+			terminal::Clear(terminal::ClearType::All)
+		)?;
+
+		// Raw mode doesn't translate newlines into carriage returns, so we do it ourselves
+		for line in frame.lines() {
+			write!(stdout, "{}\r\n", line)?;
+		}
+
+		let status = if self.autoplay { "playing" } else { "paused" };
+		write!(
+			stdout,
+			"Speed: {} | {} | Overlay: {} | Length: {}\r\n",
+			self.speed,
+			status,
+			if self.overlay { "on" } else { "off" },
+			self.world.world().snake_length()
+		)?;
+		write!(
+			stdout,
+			"Space: play/pause, +/-: speed, 1-4: run 1/10/100/1000*speed iterations, o: overlay, q: quit\r\n"
+		)?;
+
+		stdout.flush()
+	}
+}
@@ -30,7 +30,7 @@ pub fn run<S: SnakeSolver>(world_size: usize, mut solver: S) -> Duration {
 	let mut get_next_path = |world: &SnakeWorld| {
 		pathfinds += 1;
 		let start = Instant::now();
-		let path = solver.get_next_path(&world);
+		let path = solver.get_next_path(world);
 		total_duration += start.elapsed();
 		path
 	};
@@ -40,7 +40,7 @@ pub fn run<S: SnakeSolver>(world_size: usize, mut solver: S) -> Duration {
 		if let Some(next) = path.pop() {
 			return next;
 		} else {
-			path = get_next_path(&world);
+			path = get_next_path(world);
 			if path.is_empty() {
 				panic!("Solver returned empty path");
 			}
@@ -66,6 +66,7 @@ enum Solvers {
 	ZigZag,
 	StaticHamiltonian,
 	DynamicHamiltonian,
+	IncrementalDynamicHamiltonian,
 }
 
 impl Display for Solvers {
@@ -74,6 +75,7 @@ impl Display for Solvers {
 			Solvers::ZigZag => write!(f, "Zig-Zag"),
 			Solvers::StaticHamiltonian => write!(f, "Static Hamiltonian"),
 			Solvers::DynamicHamiltonian => write!(f, "Dynamic Hamiltonian"),
+			Solvers::IncrementalDynamicHamiltonian => write!(f, "Incremental Dynamic Hamiltonian"),
 		}
 	}
 }
@@ -83,6 +85,7 @@ pub fn main() {
 		Solvers::ZigZag,
 		Solvers::StaticHamiltonian,
 		Solvers::DynamicHamiltonian,
+		Solvers::IncrementalDynamicHamiltonian,
 	];
 
 	let starting_size: usize = 10;
@@ -110,6 +113,11 @@ pub fn main() {
 						world_size,
 						SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10)),
 					),
+					Solvers::IncrementalDynamicHamiltonian => run(
+						world_size,
+						SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10))
+							.with_incremental(true),
+					),
 				};
 
 				results[i][size_index] += duration;
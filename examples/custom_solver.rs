@@ -0,0 +1,82 @@
+use snake_solver::{
+	auto::{AutoPlayerState, AutoSnakePlayer, Path},
+	snake::SnakeWorld,
+	solvers::{
+		snake_spanning_tree::{
+			build_spanning_tree, pathfinding::PathfindingGrid, spanning_tree::SpanningTree,
+			TreeGrowthStrategy,
+		},
+		SnakeSolver,
+	},
+	ui::SnakeWorldViewer,
+};
+
+/// A solver built on the public spanning tree API. It rebuilds the tree for every food like
+/// `SnakeSpanningTreeSolver`, but re-plans after a single step whenever the tree's cycle doesn't
+/// cover the whole world or the path is more than twice the shortest distance to the food.
+struct CheckedTreeSolver {
+	tree: Option<SpanningTree>,
+	grid: Option<PathfindingGrid>,
+}
+
+impl SnakeSolver for CheckedTreeSolver {
+	fn get_next_path(&mut self, world: &SnakeWorld) -> Path {
+		let tree = self
+			.tree
+			.get_or_insert_with(|| SpanningTree::new(world.size(), TreeGrowthStrategy::Compact));
+		let grid = self
+			.grid
+			.get_or_insert_with(|| PathfindingGrid::new(world.size()));
+
+		build_spanning_tree(world, tree, grid).expect("Pathfinding to the food failed");
+
+		let path = tree.build_snake_path(world);
+		let spans_world = tree.build_cycle_path(world.snake_head_coord()).is_some();
+		let shortest = grid
+			.shortest_distance_from(world.snake_head_coord())
+			.unwrap_or(0) as usize;
+
+		if !spans_world || path.len() > shortest * 2 {
+			let mut first_step = Path::new();
+			first_step.push(path.iter_directions().next().unwrap());
+			first_step
+		} else {
+			path
+		}
+	}
+
+	fn decorate_widget<'a>(&'a self, widget: SnakeWorldViewer<'a>) -> SnakeWorldViewer<'a> {
+		widget
+	}
+}
+
+/// Plays a few games with a solver built on the spanning tree API, printing the steps taken.
+///
+/// Usage: `cargo run --release --example custom_solver -- [world size]`
+fn main() {
+	let grid_size = std::env::args()
+		.nth(1)
+		.map(|arg| arg.parse::<usize>().expect("World size must be a number"))
+		.unwrap_or(20);
+
+	for game in 0..5 {
+		let mut player = AutoSnakePlayer::new(
+			grid_size,
+			CheckedTreeSolver {
+				tree: None,
+				grid: None,
+			},
+		)
+		.with_max_steps_without_food((grid_size * grid_size) as u64);
+		while player.state() == AutoPlayerState::Playing {
+			player.step();
+		}
+
+		println!(
+			"Game {}: {:?} after {} steps",
+			game,
+			player.state(),
+			player.stats().total_steps
+		);
+	}
+}
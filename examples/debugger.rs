@@ -0,0 +1,318 @@
+use std::sync::{Arc, Mutex};
+
+use snake_solver::{
+	array2d::Array2D,
+	auto::Path,
+	direction::Direction,
+	grid_graph::GridGraph,
+	snake::{SnakeResult, SnakeWorld},
+	solvers::{
+		snake_spanning_tree::{
+			pathfinding::PathfindingGrid,
+			spanning_tree::{SpanTreeEdgeType, SpanningTree},
+			BuildPhase, JitterKind, SnakeSpanningTreeSolver,
+		},
+		SnakeSolver,
+	},
+	ui::SnakeWorldViewer,
+	Coord,
+};
+
+use eframe::egui::{self};
+
+const DEFAULT_GRID_SIZE: usize = 20;
+
+/// Step through the dynamic solver's phases one at a time, hovering over cells to inspect the
+/// spanning tree and pathfinding grid.
+///
+/// Usage: `cargo run --release --example debugger -- [world size] [solver options]`
+///
+/// Solver options: `--jitter <steps>`, `--incremental`, `--no-detours` (with `--incremental`),
+/// `--endgame <percent>`
+fn main() {
+	let mut args = std::env::args().skip(1).peekable();
+	let grid_size = args
+		.next_if(|arg| !arg.starts_with("--"))
+		.map(|arg| arg.parse::<usize>().expect("World size must be a number"))
+		.unwrap_or(DEFAULT_GRID_SIZE);
+
+	let mut settings = SolverSettings::default();
+	while let Some(arg) = args.next() {
+		let mut number = |name: &str| {
+			args.next()
+				.and_then(|value| value.parse::<usize>().ok())
+				.unwrap_or_else(|| panic!("{} needs a number", name))
+		};
+
+		match arg.as_str() {
+			"--jitter" => settings.jitter = JitterKind::JitterWhenIndirect(number("--jitter")),
+			"--incremental" => settings.incremental = true,
+			"--no-detours" => settings.remove_detours = true,
+			"--endgame" => settings.endgame = Some(number("--endgame")),
+			_ => panic!("Unknown argument `{}`", arg),
+		}
+	}
+
+	let width: f32 = SnakeWorldViewer::calculate_size_for_world_size(grid_size) + 20.0;
+
+	let extra_height = 80.0;
+
+	let options = eframe::NativeOptions {
+		min_window_size: Some(egui::vec2(width.max(400.0), width + extra_height)),
+		..Default::default()
+	};
+
+	eframe::run_native(
+		"Solver debugger",
+		options,
+		Box::new(move |_cc| Box::new(MyApp::new(grid_size, settings))),
+	);
+}
+
+/// The settings the debugged solver is created with.
+#[derive(Debug, Copy, Clone)]
+struct SolverSettings {
+	jitter: JitterKind,
+	incremental: bool,
+	remove_detours: bool,
+	endgame: Option<usize>,
+}
+
+impl Default for SolverSettings {
+	fn default() -> Self {
+		Self {
+			jitter: JitterKind::NoJitter,
+			incremental: false,
+			remove_detours: false,
+			endgame: None,
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+	Build(BuildPhase),
+	/// The solver returned the path the snake follows.
+	BuildPath,
+}
+
+impl Stage {
+	fn name(&self) -> &'static str {
+		match self {
+			Stage::Build(BuildPhase::TraceSnake) => "Trace snake",
+			Stage::Build(BuildPhase::FillPathfindingGrid) => "Fill pathfinding grid",
+			Stage::Build(BuildPhase::Pathfind) => "Pathfind",
+			Stage::Build(BuildPhase::GrowTree) => "Grow tree",
+			Stage::Build(BuildPhase::RepairTree) => "Repair tree",
+			Stage::Build(BuildPhase::RemoveDetours) => "Remove detours",
+			Stage::Build(BuildPhase::FollowEndgameCycle) => "Follow endgame cycle",
+			Stage::BuildPath => "Build path",
+		}
+	}
+}
+
+/// The solver's state at the end of a stage.
+struct Snapshot {
+	stage: Stage,
+	edges: GridGraph<SpanTreeEdgeType>,
+	walls: GridGraph<bool>,
+	pathfinding: Array2D<u32>,
+	path: Option<Path>,
+}
+
+impl Snapshot {
+	fn new(stage: Stage, tree: &SpanningTree, grid: &PathfindingGrid, path: Option<Path>) -> Self {
+		Self {
+			stage,
+			edges: tree.edges().clone(),
+			walls: tree.build_collision_grid_from_walls(),
+			pathfinding: grid.distances().clone(),
+			path,
+		}
+	}
+
+	/// Describe the tree node and pathfinding value of a cell, for the hover tooltip.
+	fn describe_cell(&self, coord: Coord) -> String {
+		let node = coord.map_values(|v| v / 2);
+		let mut text = format!(
+			"Cell ({}, {}), node ({}, {})",
+			coord.x, coord.y, node.x, node.y
+		);
+
+		for dir in Direction::each() {
+			if let Some(edge) = self.edges.get_edge(node, dir) {
+				text += &format!("\n{:?} edge: {:?}", dir, edge);
+			}
+		}
+
+		text += &match self.pathfinding.get(coord) {
+			Some(0) => "\nNot reached by the pathfinding fill".to_string(),
+			Some(&u32::MAX) => "\nQueued but not reached by the pathfinding fill".to_string(),
+			Some(value) => format!("\nPathfinding distance: {}", value - 1),
+			None => String::new(),
+		};
+
+		text
+	}
+}
+
+struct MyApp {
+	snake_world: SnakeWorld,
+	solver: SnakeSpanningTreeSolver,
+	/// The snapshots taken by the solver's phase hook while it plans the next path.
+	phases: Arc<Mutex<Vec<Snapshot>>>,
+	snapshots: Vec<Snapshot>,
+	current: usize,
+	path: Path,
+	result: SnakeResult,
+}
+
+impl MyApp {
+	fn new(grid_size: usize, settings: SolverSettings) -> Self {
+		let phases = Arc::new(Mutex::new(Vec::new()));
+		let hook_phases = phases.clone();
+
+		let mut solver = SnakeSpanningTreeSolver::new(settings.jitter)
+			.with_incremental(settings.incremental)
+			.with_detour_removal(settings.remove_detours)
+			.with_phase_hook(move |phase, tree, grid| {
+				hook_phases.lock().unwrap().push(Snapshot::new(
+					Stage::Build(phase),
+					tree,
+					grid,
+					None,
+				));
+			});
+		if let Some(percent) = settings.endgame {
+			solver = solver.with_endgame(percent);
+		}
+
+		let mut app = Self {
+			snake_world: SnakeWorld::new(grid_size),
+			solver,
+			phases,
+			snapshots: Vec::new(),
+			current: 0,
+			path: Path::new(),
+			result: SnakeResult::Stepped,
+		};
+		app.plan();
+		app
+	}
+
+	/// Ask the solver for the next path, recording every phase along the way.
+	fn plan(&mut self) {
+		self.path = self.solver.get_next_path(&self.snake_world);
+
+		let mut snapshots = std::mem::take(&mut *self.phases.lock().unwrap());
+		if let Some(last) = snapshots.last() {
+			snapshots.push(Snapshot {
+				stage: Stage::BuildPath,
+				edges: last.edges.clone(),
+				walls: last.walls.clone(),
+				pathfinding: last.pathfinding.clone(),
+				path: Some(self.path.clone()),
+			});
+		}
+
+		self.snapshots = snapshots;
+		self.current = 0;
+	}
+
+	/// Whether the planned path can be followed, which it can't if pathfinding failed.
+	fn path_is_safe(&self) -> bool {
+		self.snake_world
+			.forecast_occupancy(&self.path)
+			.is_path_safe()
+	}
+
+	/// Move the snake along the first `steps` steps of the planned path, then plan again.
+	fn follow_path(&mut self, steps: usize) {
+		let path = self.path.clone();
+		for dir in path.iter_directions().take(steps) {
+			self.result = self.snake_world.step_snake(dir);
+			if self.result != SnakeResult::Stepped {
+				return;
+			}
+		}
+
+		self.plan();
+	}
+}
+
+impl eframe::App for MyApp {
+	fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+		let last = self.snapshots.len().saturating_sub(1);
+		let playing = self.result == SnakeResult::Stepped;
+
+		if ctx.input().key_pressed(egui::Key::ArrowLeft) {
+			self.current = self.current.saturating_sub(1);
+		}
+		if ctx.input().key_pressed(egui::Key::ArrowRight) {
+			self.current = (self.current + 1).min(last);
+		}
+
+		egui::CentralPanel::default().show(ctx, |ui| {
+			ui.horizontal(|ui| {
+				if ui.button("< Phase").clicked() {
+					self.current = self.current.saturating_sub(1);
+				}
+				if ui.button("Phase >").clicked() {
+					self.current = (self.current + 1).min(last);
+				}
+				if ui
+					.add_enabled(playing, egui::Button::new("Step snake"))
+					.clicked()
+				{
+					self.follow_path(1);
+				}
+				if ui
+					.add_enabled(playing, egui::Button::new("Follow path"))
+					.clicked()
+				{
+					self.follow_path(usize::MAX);
+				}
+			});
+
+			let Some(snapshot) = self.snapshots.get(self.current) else {
+				return;
+			};
+
+			let status = match self.result {
+				SnakeResult::Stepped if !self.path_is_safe() => " (pathfinding failed)".to_string(),
+				SnakeResult::Stepped => String::new(),
+				SnakeResult::Killed => " (the snake died)".to_string(),
+				SnakeResult::Finished => " (finished)".to_string(),
+			};
+			ui.label(format!(
+				"Phase {}/{}: {}{}",
+				self.current + 1,
+				self.snapshots.len(),
+				snapshot.stage.name(),
+				status
+			));
+
+			let mut widget = SnakeWorldViewer::new(&self.snake_world)
+				.with_pathfinding_grid_overlay(&snapshot.pathfinding)
+				.with_bools_edges_grid_overlay(
+					&snapshot.walls,
+					egui::Color32::from_rgb(0, 255, 255),
+				);
+			if let Some(path) = &snapshot.path {
+				widget = widget.with_path_overlay(path);
+			}
+
+			let response = ui.add(widget);
+
+			// Work out which cell is under the mouse for the tooltip
+			let world_size = self.snake_world.size();
+			let cell_size =
+				SnakeWorldViewer::calculate_size_for_world_size(world_size) / world_size as f32;
+			if let Some(pos) = response.hover_pos() {
+				let offset = (pos - response.rect.min) / cell_size;
+				let coord = Coord::new(offset.x.floor() as i32, offset.y.floor() as i32);
+				response.on_hover_text(snapshot.describe_cell(coord));
+			}
+		});
+	}
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+use snake_solver::{
+	auto::AutoSnakePlayer,
+	solvers::{
+		random_spanning_tree::RandomSpanningTreeSolver,
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+		SnakeSolver,
+	},
+	ui::SnakeWorldViewer,
+};
+
+/// Regenerates SVG diagrams of the spanning tree solvers part way through a game: the bare
+/// world, the world with the planned path, and the world with all of the solver's overlays.
+///
+/// Usage: `cargo run --release --example diagrams -- [world size] [output dir] [steps]`
+fn main() -> std::io::Result<()> {
+	let mut args = std::env::args().skip(1);
+	let grid_size = args
+		.next()
+		.map(|arg| arg.parse::<usize>().expect("World size must be a number"))
+		.unwrap_or(16);
+	let output_dir = PathBuf::from(args.next().unwrap_or_else(|| "diagrams".to_string()));
+	let steps = args
+		.next()
+		.map(|arg| arg.parse::<usize>().expect("Steps must be a number"))
+		.unwrap_or(100);
+
+	std::fs::create_dir_all(&output_dir)?;
+
+	write_diagrams(
+		&output_dir,
+		"static_hamiltonian",
+		grid_size,
+		steps,
+		RandomSpanningTreeSolver::new(),
+	)?;
+	write_diagrams(
+		&output_dir,
+		"dynamic_hamiltonian",
+		grid_size,
+		steps,
+		SnakeSpanningTreeSolver::new(JitterKind::NoJitter),
+	)?;
+
+	println!("Wrote diagrams to {}", output_dir.display());
+
+	Ok(())
+}
+
+fn write_diagrams(
+	output_dir: &Path,
+	name: &str,
+	grid_size: usize,
+	steps: usize,
+	solver: impl SnakeSolver,
+) -> std::io::Result<()> {
+	let mut player = AutoSnakePlayer::new(grid_size, solver);
+	for _ in 0..steps {
+		player.step();
+	}
+
+	let scale = 4.0;
+
+	SnakeWorldViewer::new(player.world())
+		.save_svg(output_dir.join(format!("{}_world.svg", name)), scale)?;
+
+	SnakeWorldViewer::new(player.world())
+		.with_path_overlay(player.current_path())
+		.save_svg(output_dir.join(format!("{}_path.svg", name)), scale)?;
+
+	let widget = SnakeWorldViewer::new(player.world()).with_path_overlay(player.current_path());
+	player
+		.solver
+		.decorate_widget(widget)
+		.save_svg(output_dir.join(format!("{}_overlays.svg", name)), scale)?;
+
+	Ok(())
+}
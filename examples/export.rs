@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use snake_solver::{
+	auto::{AutoPlayerState, AutoSnakePlayer},
+	solvers::{
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+		SnakeSolver,
+	},
+	ui::{GifWriter, RgbaImage, SnakeWorldViewer},
+};
+
+/// Plays a full game without a display, writing the final board as a PNG and
+/// the whole game (with solver overlays) as an animated GIF.
+///
+/// Usage: `cargo run --release --example export -- [world size] [output dir] [steps per frame]`
+fn main() -> std::io::Result<()> {
+	let mut args = std::env::args().skip(1);
+	let grid_size = args
+		.next()
+		.map(|arg| arg.parse::<usize>().expect("World size must be a number"))
+		.unwrap_or(20);
+	let output_dir = PathBuf::from(args.next().unwrap_or_else(|| "export".to_string()));
+	let steps_per_frame = args
+		.next()
+		.map(|arg| {
+			arg.parse::<usize>()
+				.expect("Steps per frame must be a number")
+		})
+		.unwrap_or(10);
+
+	std::fs::create_dir_all(&output_dir)?;
+
+	let scale = 2;
+	let mut player = AutoSnakePlayer::new(
+		grid_size,
+		SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10)),
+	);
+
+	let render = |player: &AutoSnakePlayer<SnakeSpanningTreeSolver>| -> RgbaImage {
+		let widget = SnakeWorldViewer::new(player.world()).with_path_overlay(player.current_path());
+		player.solver.decorate_widget(widget).render_image(scale)
+	};
+
+	let first_frame = render(&player);
+	let mut gif = GifWriter::create(
+		output_dir.join("game.gif"),
+		first_frame.width(),
+		first_frame.height(),
+		5,
+	)?;
+	gif.add_frame(&first_frame)?;
+
+	let mut frames = 1;
+	while player.state() == AutoPlayerState::Playing {
+		for _ in 0..steps_per_frame {
+			player.step();
+		}
+
+		gif.add_frame(&render(&player))?;
+		frames += 1;
+	}
+
+	let final_frame = render(&player);
+	final_frame.save_png(output_dir.join("final.png"))?;
+
+	println!(
+		"Game ended as {:?}, wrote {} GIF frames and the final board to {}",
+		player.state(),
+		frames,
+		output_dir.display()
+	);
+
+	Ok(())
+}
@@ -1,49 +1,479 @@
-use snake_solver::{direction::Direction, snake::SnakeWorld, ui::SnakeWorldViewer};
+use std::{
+	collections::VecDeque,
+	sync::mpsc::{self, Receiver, Sender},
+	thread,
+	time::{Duration, Instant},
+};
+
+use snake_solver::{
+	array2d::Array2D,
+	auto::Path,
+	direction::Direction,
+	snake::{Cell, SnakeResult, SnakeWorld},
+	solvers::{
+		basic::BasicSnakeSolver,
+		random_spanning_tree::RandomSpanningTreeSolver,
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+		SnakeSolver,
+	},
+	ui::SnakeWorldViewer,
+};
 
 use eframe::egui::{self};
 
-const GRID_SIZE: i32 = 80;
+const DEFAULT_GRID_SIZE: usize = 20;
+const DEFAULT_TICK_RATE: u32 = 8;
+
+/// How many turns can be pressed ahead of the snake before more key presses are ignored.
+const MAX_QUEUED_TURNS: usize = 3;
 
+/// Play snake with the arrow keys.
+///
+/// Usage: `cargo run --release --example manual -- [world size] [ticks per second] [assist solver]`
+///
+/// The assist solver is one of `zigzag`, `static`, `dynamic` (the default) or `dynamic-incremental`.
 fn main() {
-	let width: f32 = SnakeWorldViewer::calculate_size_for_world_size(GRID_SIZE as usize) + 20.0;
+	let mut args = std::env::args().skip(1);
+	let grid_size = args
+		.next()
+		.map(|arg| arg.parse::<usize>().expect("World size must be a number"))
+		.unwrap_or(DEFAULT_GRID_SIZE);
+	let tick_rate = args
+		.next()
+		.map(|arg| arg.parse::<u32>().expect("Tick rate must be a number"))
+		.unwrap_or(DEFAULT_TICK_RATE);
+	let solver = args
+		.next()
+		.map(|arg| AssistSolver::parse(&arg).expect("Unknown assist solver"))
+		.unwrap_or(AssistSolver::Dynamic);
+
+	let width: f32 = SnakeWorldViewer::calculate_size_for_world_size(grid_size) + 20.0;
+
+	let extra_height = 100.0;
+
 	let options = eframe::NativeOptions {
-		min_window_size: Some(egui::vec2(width, width)),
+		min_window_size: Some(egui::vec2(width.max(300.0), width + extra_height)),
 		..Default::default()
 	};
 
 	eframe::run_native(
 		"Manual snake game",
 		options,
-		Box::new(|_cc| Box::new(MyApp::default())),
+		Box::new(move |cc| {
+			Box::new(MyApp::new(
+				grid_size,
+				tick_rate,
+				solver,
+				cc.egui_ctx.clone(),
+			))
+		}),
 	);
 }
 
+/// The solvers that can give hints in assist mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssistSolver {
+	ZigZag,
+	Static,
+	Dynamic,
+	IncrementalDynamic,
+}
+
+impl AssistSolver {
+	fn parse(name: &str) -> Option<Self> {
+		match name {
+			"zigzag" => Some(Self::ZigZag),
+			"static" => Some(Self::Static),
+			"dynamic" => Some(Self::Dynamic),
+			"dynamic-incremental" => Some(Self::IncrementalDynamic),
+			_ => None,
+		}
+	}
+
+	fn create(self) -> Box<dyn SnakeSolver + Send> {
+		match self {
+			Self::ZigZag => Box::new(BasicSnakeSolver),
+			Self::Static => Box::new(RandomSpanningTreeSolver::new()),
+			Self::Dynamic => Box::new(SnakeSpanningTreeSolver::new(JitterKind::NoJitter)),
+			Self::IncrementalDynamic => {
+				Box::new(SnakeSpanningTreeSolver::new(JitterKind::NoJitter).with_incremental(true))
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameState {
+	/// Waiting for the first arrow key before the snake starts moving.
+	Ready,
+	Playing,
+	Paused,
+	Won,
+	Died,
+}
+
+/// A move that would leave the snake less room than its own length.
+struct TrapWarning {
+	direction: Direction,
+	reachable: usize,
+}
+
+/// The solver's advice for the world on a given tick.
+struct Hint {
+	/// The solver's path, if it's safe to follow. The solver can't always plan from a position
+	/// the human got into, so its paths are only ever advice.
+	path: Option<Path>,
+	/// The tick the hint was planned on. It still applies on later ticks if the snake has only
+	/// moved along its path since.
+	tick: u64,
+}
+
+/// A snapshot of the world for the hint worker to plan from.
+struct HintRequest {
+	world: SnakeWorld,
+	tick: u64,
+}
+
+/// Runs the assist solver on its own thread, so planning never holds up the game. The worker
+/// plans from the latest world it has been sent, and skips any the snake has already left.
+struct HintWorker {
+	requests: Sender<HintRequest>,
+	hints: Receiver<Hint>,
+}
+
+impl HintWorker {
+	fn spawn(solver: AssistSolver, ctx: egui::Context) -> Self {
+		let (requests, request_receiver) = mpsc::channel::<HintRequest>();
+		let (hint_sender, hints) = mpsc::channel();
+
+		// The worker stops once the game drops its end of either channel
+		thread::spawn(move || {
+			let mut solver = solver.create();
+			while let Ok(mut request) = request_receiver.recv() {
+				while let Ok(newer) = request_receiver.try_recv() {
+					request = newer;
+				}
+
+				let path = solver.get_next_path(&request.world);
+				let hint = Hint {
+					path: request
+						.world
+						.forecast_occupancy(&path)
+						.is_path_safe()
+						.then_some(path),
+					tick: request.tick,
+				};
+				if hint_sender.send(hint).is_err() {
+					break;
+				}
+				ctx.request_repaint();
+			}
+		});
+
+		Self { requests, hints }
+	}
+}
+
 struct MyApp {
 	snake_world: SnakeWorld,
+	state: GameState,
+	heading: Option<Direction>,
+	queued_turns: VecDeque<Direction>,
+	score: u32,
+	tick_rate: u32,
+	ticks: u64,
+	/// The direction the snake moved in on each tick.
+	moves: Vec<Direction>,
+	last_tick: Instant,
+	ctx: egui::Context,
+	solver: AssistSolver,
+	hint_worker: HintWorker,
+	/// The last tick the hint worker was sent a world for.
+	requested_tick: Option<u64>,
+	assist: bool,
+	hint: Option<Hint>,
+	/// The move warnings for the current tick, along with the tick.
+	traps: Option<(u64, Vec<TrapWarning>)>,
 }
 
-impl Default for MyApp {
-	fn default() -> Self {
+impl MyApp {
+	fn new(grid_size: usize, tick_rate: u32, solver: AssistSolver, ctx: egui::Context) -> Self {
 		Self {
-			snake_world: SnakeWorld::new(GRID_SIZE as usize),
+			snake_world: SnakeWorld::new(grid_size),
+			state: GameState::Ready,
+			heading: None,
+			queued_turns: VecDeque::new(),
+			score: 0,
+			tick_rate,
+			ticks: 0,
+			moves: Vec::new(),
+			last_tick: Instant::now(),
+			hint_worker: HintWorker::spawn(solver, ctx.clone()),
+			ctx,
+			solver,
+			requested_tick: None,
+			assist: false,
+			hint: None,
+			traps: None,
+		}
+	}
+
+	/// Start a new game on a world of the same size, keeping the settings. The new game gets a
+	/// fresh worker, so no hints for the old game can arrive.
+	fn restart(&mut self) {
+		*self = Self {
+			assist: self.assist,
+			..Self::new(
+				self.snake_world.size(),
+				self.tick_rate,
+				self.solver,
+				self.ctx.clone(),
+			)
+		};
+	}
+
+	/// Queue a turn to be taken on a later tick. Turns that wouldn't change the snake's heading,
+	/// or would reverse it into its own neck, are ignored.
+	fn queue_turn(&mut self, direction: Direction) {
+		let last_heading = self.queued_turns.back().copied().or(self.heading);
+		if self.queued_turns.len() >= MAX_QUEUED_TURNS
+			|| last_heading == Some(direction)
+			|| last_heading == Some(direction.opposite())
+		{
+			return;
+		}
+
+		self.queued_turns.push_back(direction);
+		if self.state == GameState::Ready {
+			self.state = GameState::Playing;
+			self.last_tick = Instant::now();
+		}
+	}
+
+	fn tick_interval(&self) -> Duration {
+		Duration::from_secs_f64(1.0 / self.tick_rate.max(1) as f64)
+	}
+
+	/// Move the snake one cell, taking the next queued turn if there is one.
+	fn tick(&mut self) {
+		let Some(direction) = self.queued_turns.pop_front().or(self.heading) else {
+			return;
+		};
+		self.heading = Some(direction);
+		self.ticks += 1;
+		self.moves.push(direction);
+
+		let length_before = self.snake_world.snake_length();
+		let result = self.snake_world.step_snake(direction);
+		if self.snake_world.snake_length() != length_before {
+			self.score += 1;
+		}
+
+		self.state = match result {
+			SnakeResult::Stepped => GameState::Playing,
+			SnakeResult::Finished => GameState::Won,
+			SnakeResult::Killed => GameState::Died,
+		};
+	}
+
+	/// Send the world to the hint worker if it changed, and pick up the hints it has finished.
+	fn update_hint(&mut self) {
+		if self.requested_tick != Some(self.ticks) {
+			self.requested_tick = Some(self.ticks);
+			// If the worker has stopped there are no more hints, but the game carries on
+			let _ = self.hint_worker.requests.send(HintRequest {
+				world: self.snake_world.clone(),
+				tick: self.ticks,
+			});
+		}
+
+		if let Some(hint) = self.hint_worker.hints.try_iter().last() {
+			self.hint = Some(hint);
+		}
+	}
+
+	/// The latest hint's path from the snake's current position, as long as the snake has only
+	/// followed it since it was planned. The outer `None` means there's no up to date hint yet,
+	/// and the inner `None` means the solver had no safe path to suggest.
+	fn current_hint_path(&self) -> Option<Option<Path>> {
+		let hint = self.hint.as_ref()?;
+		let moved = self.moves.get(hint.tick as usize..)?;
+		let Some(path) = &hint.path else {
+			return moved.is_empty().then_some(None);
+		};
+
+		let mut steps = path.iter_directions();
+		if !moved.iter().all(|&dir| steps.next() == Some(dir)) {
+			return None;
+		}
+
+		let mut rest = Path::new();
+		for dir in steps {
+			rest.push(dir);
 		}
+		(!rest.is_empty()).then_some(Some(rest))
+	}
+
+	/// Warn about every move that would leave the snake less room than its own length. These are
+	/// cheap, so they're worked out on the game's thread once per tick.
+	fn current_traps(&mut self) -> &[TrapWarning] {
+		if self.traps.as_ref().map(|(tick, _)| *tick) != Some(self.ticks) {
+			let world = &self.snake_world;
+			let traps = Direction::each()
+				.filter_map(|direction| {
+					let mut next_world = world.clone();
+					if next_world.step_snake(direction) != SnakeResult::Stepped {
+						return None;
+					}
+
+					let reachable = count_reachable_cells(&next_world);
+					(reachable < next_world.snake_length() as usize).then_some(TrapWarning {
+						direction,
+						reachable,
+					})
+				})
+				.collect();
+			self.traps = Some((self.ticks, traps));
+		}
+
+		&self.traps.as_ref().unwrap().1
+	}
+
+	fn handle_input(&mut self, ctx: &egui::Context) {
+		let input = ctx.input();
+
+		for (key, direction) in [
+			(egui::Key::ArrowUp, Direction::Up),
+			(egui::Key::ArrowDown, Direction::Down),
+			(egui::Key::ArrowLeft, Direction::Left),
+			(egui::Key::ArrowRight, Direction::Right),
+		] {
+			if input.key_pressed(key) && matches!(self.state, GameState::Ready | GameState::Playing)
+			{
+				self.queue_turn(direction);
+			}
+		}
+
+		if input.key_pressed(egui::Key::H) {
+			self.assist = !self.assist;
+		}
+
+		if input.key_pressed(egui::Key::P) || input.key_pressed(egui::Key::Space) {
+			self.state = match self.state {
+				GameState::Playing => GameState::Paused,
+				GameState::Paused => {
+					self.last_tick = Instant::now();
+					GameState::Playing
+				}
+				state => state,
+			};
+		}
+
+		let restart = input.key_pressed(egui::Key::R) || input.key_pressed(egui::Key::Enter);
+		drop(input);
+
+		if restart && matches!(self.state, GameState::Won | GameState::Died) {
+			self.restart();
+		}
+	}
+}
+
+/// Count the cells the head can reach without crossing the snake's body.
+fn count_reachable_cells(world: &SnakeWorld) -> usize {
+	let mut visited = Array2D::new(world.size(), false);
+	let mut stack = vec![world.snake_head_coord()];
+	let mut count = 0;
+
+	while let Some(coord) = stack.pop() {
+		for next in Direction::each().map(|dir| coord.go_towards(dir)) {
+			if matches!(world.get_cell(next), Some(Cell::Empty | Cell::Food)) && !visited[next] {
+				visited[next] = true;
+				count += 1;
+				stack.push(next);
+			}
+		}
+	}
+
+	count
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+	match direction {
+		Direction::Up => "up",
+		Direction::Down => "down",
+		Direction::Left => "left",
+		Direction::Right => "right",
 	}
 }
 
 impl eframe::App for MyApp {
 	fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-		// Sense arrow keys
-		if ctx.input().key_pressed(egui::Key::ArrowUp) {
-			self.snake_world.step_snake(Direction::Up);
-		} else if ctx.input().key_pressed(egui::Key::ArrowDown) {
-			self.snake_world.step_snake(Direction::Down);
-		} else if ctx.input().key_pressed(egui::Key::ArrowLeft) {
-			self.snake_world.step_snake(Direction::Left);
-		} else if ctx.input().key_pressed(egui::Key::ArrowRight) {
-			self.snake_world.step_snake(Direction::Right);
-		}
-
-		egui::CentralPanel::default()
-			.show(ctx, |ui| ui.add(SnakeWorldViewer::new(&self.snake_world)));
+		self.handle_input(ctx);
+
+		if self.state == GameState::Playing && self.last_tick.elapsed() >= self.tick_interval() {
+			self.last_tick = Instant::now();
+			self.tick();
+		}
+
+		egui::CentralPanel::default().show(ctx, |ui| {
+			ui.horizontal(|ui| {
+				ui.label(format!("Score: {}", self.score));
+				ui.label("Speed");
+				ui.add(egui::Slider::new(&mut self.tick_rate, 1..=30).suffix(" ticks/s"));
+				ui.add(egui::Checkbox::new(&mut self.assist, "Assist (H)"));
+			});
+
+			match self.state {
+				GameState::Ready => ui.label("Press an arrow key to start"),
+				GameState::Playing => ui.label("P or space to pause"),
+				GameState::Paused => ui.heading("Paused"),
+				GameState::Won => ui.heading("You won! Press R to play again"),
+				GameState::Died => ui.heading("Game over! Press R to restart"),
+			};
+
+			let playing = matches!(
+				self.state,
+				GameState::Ready | GameState::Playing | GameState::Paused
+			);
+			if !self.assist || !playing {
+				ui.add(SnakeWorldViewer::new(&self.snake_world));
+				return;
+			}
+
+			self.update_hint();
+			let path = self.current_hint_path();
+			match &path {
+				Some(Some(path)) => ui.label(format!(
+					"Suggested move: {}",
+					direction_name(path.iter_directions().next().unwrap())
+				)),
+				Some(None) => ui.label("The solver has no suggestion"),
+				None => ui.label("Thinking..."),
+			};
+
+			for trap in self.current_traps() {
+				ui.colored_label(
+					egui::Color32::from_rgb(255, 64, 64),
+					format!(
+						"Moving {} would trap the snake in {} cells",
+						direction_name(trap.direction),
+						trap.reachable
+					),
+				);
+			}
+
+			let mut widget = SnakeWorldViewer::new(&self.snake_world);
+			if let Some(Some(path)) = &path {
+				widget = widget.with_path_overlay(path);
+			}
+			ui.add(widget);
+		});
+
+		if self.state == GameState::Playing {
+			ctx.request_repaint_after(
+				self.tick_interval()
+					.saturating_sub(self.last_tick.elapsed()),
+			);
+		}
 	}
 }
@@ -122,8 +122,8 @@ impl<SS: SnakeSolver> eframe::App for MyApp<SS> {
 	fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
 		egui::CentralPanel::default().show(ctx, |ui| {
 			match self.world.state() {
-				AutoPlayerState::Playing | AutoPlayerState::Killed => {
-					let mut widget = SnakeWorldViewer::new(&self.world.world());
+				AutoPlayerState::Playing | AutoPlayerState::Killed | AutoPlayerState::Stalled => {
+					let mut widget = SnakeWorldViewer::new(self.world.world());
 
 					if self.overlay {
 						widget = widget.with_path_overlay(self.world.current_path());
@@ -134,6 +134,24 @@ impl<SS: SnakeSolver> eframe::App for MyApp<SS> {
 				}
 				AutoPlayerState::Finished => {
 					ui.heading("Finished");
+
+					let stats = self.world.stats();
+					ui.label(format!("Total steps: {}", stats.total_steps));
+					ui.label(format!(
+						"Mean steps per food: {:.1}",
+						stats.mean_steps_per_food()
+					));
+					ui.label(format!(
+						"Paths computed: {} (mean length {:.1}, {} overridden)",
+						stats.path_computations,
+						stats.mean_path_length(),
+						stats.path_overrides
+					));
+					ui.label(format!(
+						"Solver time: {:?} (mean {:?} per path)",
+						stats.solver_time,
+						stats.mean_path_time()
+					));
 				}
 			}
 
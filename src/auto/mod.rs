@@ -1,7 +1,16 @@
+use std::time::Instant;
+
 pub use crate::path::*;
 
+mod observer;
+mod simulate;
+mod stats;
+pub use observer::*;
+pub use simulate::*;
+pub use stats::*;
+
 use crate::{
-	snake::{SnakeResult, SnakeWorld},
+	snake::{OccupancyForecast, SnakeResult, SnakeWorld},
 	solvers::SnakeSolver,
 };
 
@@ -10,56 +19,146 @@ pub enum AutoPlayerState {
 	Playing,
 	Finished,
 	Killed,
+	/// The player ran out of steps before the game ended, see `AutoSnakePlayer::with_max_steps`
+	/// and `AutoSnakePlayer::with_max_steps_without_food`.
+	Stalled,
 }
 
 pub struct AutoSnakePlayer<S: SnakeSolver> {
 	world: SnakeWorld,
 	current_path: Path,
 	state: AutoPlayerState,
+	stats: GameStats,
+	steps_since_food: u64,
+	max_steps: Option<u64>,
+	max_steps_without_food: Option<u64>,
+	observers: Vec<Box<dyn PlayerObserver + Send>>,
 	pub solver: S,
 }
 
 impl<S: SnakeSolver> AutoSnakePlayer<S> {
-	pub fn new(size: usize, mut solver: S) -> Self {
-		let world = SnakeWorld::new(size);
-		let initial_path = solver.get_next_path(&world);
-		Self {
-			world,
-			current_path: initial_path,
+	pub fn new(size: usize, solver: S) -> Self {
+		let mut player = Self {
+			world: SnakeWorld::new(size),
+			current_path: Path::new(),
 			state: AutoPlayerState::Playing,
+			stats: GameStats::default(),
+			steps_since_food: 0,
+			max_steps: None,
+			max_steps_without_food: None,
+			observers: Vec::new(),
 			solver,
+		};
+		player.plan_next_path();
+		player
+	}
+
+	/// Stall the player once the snake has taken this many steps without the game ending.
+	pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+		self.max_steps = Some(max_steps);
+		self
+	}
+
+	/// Stall the player once the snake has taken this many steps since it last ate, which catches
+	/// solvers that go around in circles without ever reaching the food.
+	pub fn with_max_steps_without_food(mut self, max_steps: u64) -> Self {
+		self.max_steps_without_food = Some(max_steps);
+		self
+	}
+
+	/// Send the player's events to `observer`. The first path is planned when the player is
+	/// created, so observers added before the first step are told about it straight away.
+	/// Observers must be `Send` so that the player can still be moved to another thread.
+	pub fn with_observer(mut self, mut observer: impl PlayerObserver + Send + 'static) -> Self {
+		if self.stats.total_steps == 0 && self.stats.path_computations == 1 {
+			observer.on_path_planned(&self.world, &self.current_path, self.stats.solver_time);
+		}
+		self.observers.push(Box::new(observer));
+		self
+	}
+
+	/// Ask the solver for the next path, recording the time taken and the path's length.
+	fn plan_next_path(&mut self) {
+		let start = Instant::now();
+		self.current_path = self.solver.get_next_path(&self.world);
+		let elapsed = start.elapsed();
+
+		self.stats.path_computations += 1;
+		self.stats.solver_time += elapsed;
+		self.stats.max_path_time = self.stats.max_path_time.max(elapsed);
+		self.stats.path_lengths.push(self.current_path.len());
+		if self.solver.last_path_was_overridden() {
+			self.stats.path_overrides += 1;
+		}
+
+		for observer in self.observers.iter_mut() {
+			observer.on_path_planned(&self.world, &self.current_path, elapsed);
 		}
 	}
 
+	/// Move the snake one step along the solver's path. A player that has finished, been killed
+	/// or stalled no longer moves. A stalled snake is still alive, so its steps report
+	/// `SnakeResult::Stepped`, and `state` tells a stall apart from a game that's still playing.
 	pub fn step(&mut self) -> SnakeResult {
-		if self.state == AutoPlayerState::Finished {
-			return SnakeResult::Finished;
-		} else if self.state == AutoPlayerState::Killed {
-			return SnakeResult::Killed;
+		match self.state {
+			AutoPlayerState::Playing => {}
+			AutoPlayerState::Finished => return SnakeResult::Finished,
+			AutoPlayerState::Killed => return SnakeResult::Killed,
+			AutoPlayerState::Stalled => return SnakeResult::Stepped,
 		}
 
 		let next_step = loop {
 			if let Some(next) = self.current_path.pop() {
 				break next;
 			} else {
-				self.current_path = self.solver.get_next_path(&self.world);
+				self.plan_next_path();
 				if self.current_path.is_empty() {
 					panic!("Solver returned empty path");
 				}
 			}
 		};
 
+		let length_before = self.world.snake_length();
 		let result = self.world.step_snake(next_step);
 
-		if result == SnakeResult::Finished {
-			self.state = AutoPlayerState::Finished;
-		} else if result == SnakeResult::Killed {
-			self.state = AutoPlayerState::Killed;
+		for observer in self.observers.iter_mut() {
+			observer.on_step(&self.world, next_step);
+		}
+
+		if result != SnakeResult::Killed {
+			self.stats.total_steps += 1;
+			self.steps_since_food += 1;
+
+			if self.world.snake_length() != length_before {
+				self.stats.steps_per_food.push(self.steps_since_food);
+				for observer in self.observers.iter_mut() {
+					observer.on_food_eaten(&self.world, self.steps_since_food);
+				}
+				self.steps_since_food = 0;
+			}
+		}
+
+		self.state = match result {
+			SnakeResult::Finished => AutoPlayerState::Finished,
+			SnakeResult::Killed => AutoPlayerState::Killed,
+			SnakeResult::Stepped if self.is_out_of_steps() => AutoPlayerState::Stalled,
+			SnakeResult::Stepped => return result,
+		};
+
+		for observer in self.observers.iter_mut() {
+			observer.on_game_ended(&self.world, self.state, &self.stats);
 		}
 
 		result
 	}
 
+	fn is_out_of_steps(&self) -> bool {
+		let over_budget =
+			|max_steps: Option<u64>, steps: u64| max_steps.is_some_and(|max| steps >= max);
+		over_budget(self.max_steps, self.stats.total_steps)
+			|| over_budget(self.max_steps_without_food, self.steps_since_food)
+	}
+
 	pub fn world(&self) -> &SnakeWorld {
 		&self.world
 	}
@@ -71,4 +170,19 @@ impl<S: SnakeSolver> AutoSnakePlayer<S> {
 	pub fn current_path(&self) -> &Path {
 		&self.current_path
 	}
+
+	/// When every cell will be empty if the snake follows the rest of the current path.
+	pub fn occupancy_forecast(&self) -> OccupancyForecast {
+		self.world.forecast_occupancy(&self.current_path)
+	}
+
+	/// The statistics collected so far. Once the game has ended these are the final statistics for the game.
+	pub fn stats(&self) -> &GameStats {
+		&self.stats
+	}
+
+	/// Consume the player, returning the statistics for the game.
+	pub fn into_stats(self) -> GameStats {
+		self.stats
+	}
 }
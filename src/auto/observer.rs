@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use crate::{direction::Direction, snake::SnakeWorld};
+
+use super::{AutoPlayerState, GameStats, Path};
+
+/// Receives events from an `AutoSnakePlayer` as the game is played, for logging, metrics or
+/// recording games. Every method does nothing by default, so observers only need to implement
+/// the events they care about.
+pub trait PlayerObserver {
+	/// The solver returned a new path for `world`, taking `elapsed` to plan it.
+	fn on_path_planned(&mut self, _world: &SnakeWorld, _path: &Path, _elapsed: Duration) {}
+
+	/// The snake moved in `direction`. `world` is the world after the step, which includes the
+	/// step that ended the game.
+	fn on_step(&mut self, _world: &SnakeWorld, _direction: Direction) {}
+
+	/// The snake ate the food after `steps` steps since the last food it ate.
+	fn on_food_eaten(&mut self, _world: &SnakeWorld, _steps: u64) {}
+
+	/// The game finished, the snake died or the player stalled. This is called once per game.
+	fn on_game_ended(&mut self, _world: &SnakeWorld, _state: AutoPlayerState, _stats: &GameStats) {}
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{rng::seed_thread_rng, solvers::SnakeSolver};
+
+use super::{AutoPlayerState, AutoSnakePlayer, GameStats};
+
+/// The settings shared by every game played by `simulate_many`.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulationConfig {
+	size: usize,
+	max_steps: u64,
+	max_steps_without_food: Option<u64>,
+	threads: Option<usize>,
+}
+
+impl SimulationConfig {
+	/// Games are played on a `size` by `size` world, and given up on after `2 * size^4` steps.
+	pub fn new(size: usize) -> Self {
+		Self {
+			size,
+			max_steps: 2 * (size as u64).pow(4),
+			max_steps_without_food: None,
+			threads: None,
+		}
+	}
+
+	/// Give up on games that haven't ended after this many steps.
+	pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+		self.max_steps = max_steps;
+		self
+	}
+
+	/// Give up on games where the snake goes this many steps without eating.
+	pub fn with_max_steps_without_food(mut self, max_steps: u64) -> Self {
+		self.max_steps_without_food = Some(max_steps);
+		self
+	}
+
+	/// Play the games on a pool of this many threads instead of rayon's global pool. Only used
+	/// with the `parallel` feature.
+	pub fn with_threads(mut self, threads: usize) -> Self {
+		self.threads = Some(threads.max(1));
+		self
+	}
+
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	pub fn max_steps(&self) -> u64 {
+		self.max_steps
+	}
+
+	pub fn max_steps_without_food(&self) -> Option<u64> {
+		self.max_steps_without_food
+	}
+
+	pub fn threads(&self) -> Option<usize> {
+		self.threads
+	}
+}
+
+/// The result of a single game played by `simulate_many`.
+#[derive(Debug, Clone)]
+pub struct SimulatedGame {
+	pub seed: u64,
+	/// The state the game ended in, `Stalled` if it ran out of steps.
+	pub state: AutoPlayerState,
+	pub stats: GameStats,
+}
+
+/// The games played by `simulate_many`, in the same order as the seeds they were given.
+#[derive(Debug, Clone)]
+pub struct SimulationResults {
+	pub games: Vec<SimulatedGame>,
+}
+
+impl SimulationResults {
+	fn count_state(&self, state: AutoPlayerState) -> usize {
+		self.games.iter().filter(|game| game.state == state).count()
+	}
+
+	pub fn finished_count(&self) -> usize {
+		self.count_state(AutoPlayerState::Finished)
+	}
+
+	pub fn killed_count(&self) -> usize {
+		self.count_state(AutoPlayerState::Killed)
+	}
+
+	/// The number of games that ran out of steps before the snake finished or died.
+	pub fn stalled_count(&self) -> usize {
+		self.count_state(AutoPlayerState::Stalled)
+	}
+
+	pub fn min_steps(&self) -> Option<u64> {
+		self.games.iter().map(|game| game.stats.total_steps).min()
+	}
+
+	pub fn max_steps(&self) -> Option<u64> {
+		self.games.iter().map(|game| game.stats.total_steps).max()
+	}
+
+	pub fn mean_steps(&self) -> f64 {
+		super::stats::mean(self.games.iter().map(|game| game.stats.total_steps as f64))
+	}
+
+	/// The mean number of steps per food over every food eaten in every game.
+	pub fn mean_steps_per_food(&self) -> f64 {
+		super::stats::mean(
+			self.games
+				.iter()
+				.flat_map(|game| game.stats.steps_per_food.iter())
+				.map(|&steps| steps as f64),
+		)
+	}
+
+	/// The total time spent inside the solvers' `get_next_path` across every game.
+	pub fn total_solver_time(&self) -> Duration {
+		self.games.iter().map(|game| game.stats.solver_time).sum()
+	}
+
+	pub fn mean_path_time(&self) -> Duration {
+		let paths = self
+			.games
+			.iter()
+			.map(|game| game.stats.path_computations)
+			.sum::<u64>();
+		if paths == 0 {
+			Duration::ZERO
+		} else {
+			self.total_solver_time().div_f64(paths as f64)
+		}
+	}
+
+	/// The longest single call to a solver's `get_next_path` in any game.
+	pub fn max_path_time(&self) -> Duration {
+		self.games
+			.iter()
+			.map(|game| game.stats.max_path_time)
+			.max()
+			.unwrap_or(Duration::ZERO)
+	}
+
+	pub fn path_overrides(&self) -> u64 {
+		self.games
+			.iter()
+			.map(|game| game.stats.path_overrides)
+			.sum()
+	}
+}
+
+/// Play one game for every seed, each with a fresh solver from `solver_factory`. With the
+/// `parallel` feature the games are spread across rayon's thread pool, otherwise they're played
+/// one after another. Each game is seeded and played on a single thread, so the results are
+/// the same either way.
+pub fn simulate_many<S: SnakeSolver>(
+	config: SimulationConfig,
+	seeds: impl IntoIterator<Item = u64>,
+	solver_factory: impl Fn() -> S + Sync,
+) -> SimulationResults {
+	simulate_many_with_progress(config, seeds, solver_factory, |_| {})
+}
+
+/// Like `simulate_many`, but calls `on_game_played` as soon as each game ends, e.g. to drive a
+/// progress bar. With the `parallel` feature, games end in any order and `on_game_played` may
+/// be called from several threads at once.
+pub fn simulate_many_with_progress<S: SnakeSolver>(
+	config: SimulationConfig,
+	seeds: impl IntoIterator<Item = u64>,
+	solver_factory: impl Fn() -> S + Sync,
+	on_game_played: impl Fn(&SimulatedGame) + Sync,
+) -> SimulationResults {
+	let seeds = seeds.into_iter().collect::<Vec<_>>();
+	let play = |seed| {
+		let game = simulate_game(config, seed, &solver_factory);
+		on_game_played(&game);
+		game
+	};
+
+	#[cfg(feature = "parallel")]
+	let games = {
+		let play_all = || seeds.into_par_iter().map(play).collect();
+		match config.threads {
+			Some(threads) => rayon::ThreadPoolBuilder::new()
+				.num_threads(threads)
+				.build()
+				.expect("Failed to create the simulation thread pool")
+				.install(play_all),
+			None => play_all(),
+		}
+	};
+
+	#[cfg(not(feature = "parallel"))]
+	let games = seeds.into_iter().map(play).collect();
+
+	SimulationResults { games }
+}
+
+fn simulate_game<S: SnakeSolver>(
+	config: SimulationConfig,
+	seed: u64,
+	solver_factory: &impl Fn() -> S,
+) -> SimulatedGame {
+	// The player asks for its first path straight away, so seed before creating it
+	seed_thread_rng(seed);
+	let mut player =
+		AutoSnakePlayer::new(config.size, solver_factory()).with_max_steps(config.max_steps);
+	if let Some(max_steps) = config.max_steps_without_food {
+		player = player.with_max_steps_without_food(max_steps);
+	}
+
+	while player.state() == AutoPlayerState::Playing {
+		player.step();
+	}
+
+	SimulatedGame {
+		seed,
+		state: player.state(),
+		stats: player.into_stats(),
+	}
+}
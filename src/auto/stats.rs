@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+/// Statistics collected by `AutoSnakePlayer` over the course of a single game.
+#[derive(Debug, Clone, Default)]
+pub struct GameStats {
+	/// The total number of steps the snake has taken.
+	pub total_steps: u64,
+	/// The number of steps taken to reach each food that has been eaten, in order.
+	pub steps_per_food: Vec<u64>,
+	/// The number of times the solver's `get_next_path` was called.
+	pub path_computations: u64,
+	/// The total time spent inside the solver's `get_next_path`.
+	pub solver_time: Duration,
+	/// The longest single call to the solver's `get_next_path`.
+	pub max_path_time: Duration,
+	/// The length of every path returned by the solver, in order.
+	pub path_lengths: Vec<usize>,
+	/// The number of paths where the solver reported that the spanning tree had to be
+	/// grown over the planned path (`SnakeGrowResult::SuccessWithPathOverride`).
+	pub path_overrides: u64,
+}
+
+impl GameStats {
+	pub fn foods_eaten(&self) -> usize {
+		self.steps_per_food.len()
+	}
+
+	pub fn mean_steps_per_food(&self) -> f64 {
+		mean(self.steps_per_food.iter().map(|&steps| steps as f64))
+	}
+
+	pub fn mean_path_time(&self) -> Duration {
+		if self.path_computations == 0 {
+			Duration::ZERO
+		} else {
+			self.solver_time.div_f64(self.path_computations as f64)
+		}
+	}
+
+	pub fn min_path_length(&self) -> Option<usize> {
+		self.path_lengths.iter().copied().min()
+	}
+
+	pub fn max_path_length(&self) -> Option<usize> {
+		self.path_lengths.iter().copied().max()
+	}
+
+	pub fn mean_path_length(&self) -> f64 {
+		mean(self.path_lengths.iter().map(|&len| len as f64))
+	}
+
+	/// Group the path lengths into buckets of `bucket_size`, returning the number of paths in each bucket.
+	/// Bucket `i` contains the paths with lengths from `i * bucket_size` to `(i + 1) * bucket_size - 1`.
+	pub fn path_length_histogram(&self, bucket_size: usize) -> Vec<usize> {
+		let bucket_size = bucket_size.max(1);
+		let mut buckets = vec![
+			0;
+			self.max_path_length()
+				.map_or(0, |max| max / bucket_size + 1)
+		];
+		for &len in self.path_lengths.iter() {
+			buckets[len / bucket_size] += 1;
+		}
+		buckets
+	}
+}
+
+pub(super) fn mean(values: impl Iterator<Item = f64>) -> f64 {
+	let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+	if count == 0 {
+		0.0
+	} else {
+		sum / count as f64
+	}
+}
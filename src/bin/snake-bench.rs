@@ -0,0 +1,711 @@
+use std::{
+	fmt::Write as _,
+	io::Write,
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Duration,
+};
+
+use snake_solver::{
+	auto::{
+		simulate_many_with_progress, AutoPlayerState, SimulatedGame, SimulationConfig,
+		SimulationResults,
+	},
+	solvers::{
+		basic::BasicSnakeSolver,
+		random_spanning_tree::RandomSpanningTreeSolver,
+		snake_spanning_tree::{
+			pathfinding::StepCosts, JitterKind, SnakeSpanningTreeSolver, TreeGrowthStrategy,
+		},
+	},
+};
+
+const USAGE: &str = "\
+Benchmark the snake solvers, emitting per-game results as JSON or CSV.
+
+Usage: snake-bench [options]
+
+Options:
+  --solvers <list>    Comma separated solvers to run [default: zigzag,static,dynamic,dynamic-indirect-10,dynamic-indirect-1]
+                      Available: zigzag, static, static-food-<percent>, dynamic, dynamic-indirect-<n>, dynamic-always-<n>,
+                                 dynamic-longer-<percent>-<n>, dynamic-fill-<percent>-<n>, dynamic-budget-<ms>-<n>
+                      Add -regen to a static solver to regenerate its cycle around the snake when it's shorter
+                      Add -prim, -wilson or -compact to a dynamic solver to change how its tree is grown
+                      Add -incremental to a dynamic solver to repair its tree instead of rebuilding it
+                      Add -no-detours after -incremental to rewire the repaired tree around detours in its paths
+                      Add -endgame-<percent> to a dynamic solver to follow a fixed cycle once the snake fills that much
+                      Add -costs-<clockwise>-<out>-<turn> to a dynamic solver to weigh the steps of its paths
+  --sizes <list>      Comma separated even world sizes [default: 10,20,40]
+  --seeds <seeds>     Seeds to run, either a range like 0..100 or a comma separated list [default: 0..10]
+  --threads <n>       Number of games to run in parallel, with the parallel feature [default: available cores]
+  --max-steps <n>     Steps before a game is reported as stalled [default: 2 * size^4]
+  --max-steps-without-food <n>
+                      Steps without eating before a game is reported as stalled [default: no limit]
+  --format <format>   Output format, json or csv [default: json]
+  --output <file>     Write the results to a file instead of stdout
+  -h, --help          Print this message
+";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SolverSpec {
+	ZigZag,
+	StaticHamiltonian {
+		food_bias_percent: u32,
+		regenerate: bool,
+	},
+	DynamicHamiltonian {
+		jitter: JitterKind,
+		growth: TreeGrowthStrategy,
+		incremental: bool,
+		remove_detours: bool,
+		endgame_fill: Option<usize>,
+		step_costs: StepCosts,
+	},
+}
+
+const GROWTH_STRATEGIES: [(&str, TreeGrowthStrategy); 3] = [
+	("prim", TreeGrowthStrategy::RandomizedPrim),
+	("wilson", TreeGrowthStrategy::Wilson),
+	("compact", TreeGrowthStrategy::Compact),
+];
+
+impl SolverSpec {
+	fn parse(spec: &str) -> Result<Self, String> {
+		let parse_steps = |steps: &str| {
+			steps
+				.parse::<usize>()
+				.map_err(|_| format!("Invalid jitter step count in solver `{}`", spec))
+		};
+		// Parse a `<threshold>-<steps>` pair for the adaptive jitter policies
+		let parse_threshold_and_steps = |value: &str| {
+			let (threshold, steps) = value
+				.split_once('-')
+				.ok_or_else(|| format!("Expected `<threshold>-<steps>` in solver `{}`", spec))?;
+			let threshold = threshold
+				.parse::<usize>()
+				.map_err(|_| format!("Invalid jitter threshold in solver `{}`", spec))?;
+			Ok::<_, String>((threshold, parse_steps(steps)?))
+		};
+
+		if let Some((base, costs)) = spec.rsplit_once("-costs-") {
+			let costs = costs
+				.split('-')
+				.map(|cost| cost.parse::<u32>().ok())
+				.collect::<Option<Vec<_>>>();
+			let Some(&[clockwise, out, turn]) = costs.as_deref() else {
+				return Err(format!(
+					"Expected `-costs-<clockwise>-<out>-<turn>` in solver `{}`",
+					spec
+				));
+			};
+
+			return match Self::parse(base)? {
+				Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental,
+					remove_detours,
+					endgame_fill,
+					..
+				} => Ok(Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental,
+					remove_detours,
+					endgame_fill,
+					step_costs: StepCosts {
+						clockwise,
+						out,
+						turn,
+					},
+				}),
+				_ => Err(format!("Solver `{}` has no step costs", base)),
+			};
+		}
+
+		if let Some((base, percent)) = spec.rsplit_once("-endgame-") {
+			let percent = percent
+				.parse::<usize>()
+				.ok()
+				.filter(|&percent| percent <= 100)
+				.ok_or_else(|| format!("Invalid endgame percentage in solver `{}`", spec))?;
+
+			return match Self::parse(base)? {
+				Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental,
+					remove_detours,
+					endgame_fill: None,
+					step_costs,
+				} => Ok(Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental,
+					remove_detours,
+					endgame_fill: Some(percent),
+					step_costs,
+				}),
+				_ => Err(format!("Solver `{}` has no endgame mode", base)),
+			};
+		}
+
+		if let Some(base) = spec.strip_suffix("-no-detours") {
+			return match Self::parse(base)? {
+				Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental: true,
+					remove_detours: false,
+					endgame_fill,
+					step_costs,
+				} => Ok(Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental: true,
+					remove_detours: true,
+					endgame_fill,
+					step_costs,
+				}),
+				_ => Err(format!(
+					"Solver `{}` can't remove detours, only incremental solvers can",
+					base
+				)),
+			};
+		}
+
+		if let Some(base) = spec.strip_suffix("-regen") {
+			return match Self::parse(base)? {
+				Self::StaticHamiltonian {
+					food_bias_percent,
+					regenerate: false,
+				} => Ok(Self::StaticHamiltonian {
+					food_bias_percent,
+					regenerate: true,
+				}),
+				_ => Err(format!("Solver `{}` can't regenerate its cycle", base)),
+			};
+		}
+
+		if let Some(base) = spec.strip_suffix("-incremental") {
+			return match Self::parse(base)? {
+				Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental: false,
+					remove_detours,
+					endgame_fill,
+					step_costs,
+				} => Ok(Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental: true,
+					remove_detours,
+					endgame_fill,
+					step_costs,
+				}),
+				_ => Err(format!("Solver `{}` has no incremental mode", base)),
+			};
+		}
+
+		for (name, growth) in GROWTH_STRATEGIES {
+			let Some(base) = spec.strip_suffix(&format!("-{}", name)) else {
+				continue;
+			};
+
+			return match Self::parse(base)? {
+				Self::DynamicHamiltonian {
+					jitter,
+					growth: TreeGrowthStrategy::RandomDfs,
+					incremental: false,
+					remove_detours,
+					endgame_fill,
+					step_costs,
+				} => Ok(Self::DynamicHamiltonian {
+					jitter,
+					growth,
+					incremental: false,
+					remove_detours,
+					endgame_fill,
+					step_costs,
+				}),
+				_ => Err(format!(
+					"Solver `{}` can't use the {} growth strategy",
+					base, name
+				)),
+			};
+		}
+
+		let dynamic = |jitter| {
+			Ok(Self::DynamicHamiltonian {
+				jitter,
+				growth: TreeGrowthStrategy::RandomDfs,
+				incremental: false,
+				remove_detours: false,
+				endgame_fill: None,
+				step_costs: StepCosts::default(),
+			})
+		};
+
+		match spec {
+			"zigzag" => Ok(Self::ZigZag),
+			"static" => Ok(Self::StaticHamiltonian {
+				food_bias_percent: 0,
+				regenerate: false,
+			}),
+			"dynamic" => dynamic(JitterKind::NoJitter),
+			_ => {
+				if let Some(percent) = spec.strip_prefix("static-food-") {
+					let food_bias_percent = percent
+						.parse::<u32>()
+						.ok()
+						.filter(|&percent| percent <= 100)
+						.ok_or_else(|| {
+							format!("Invalid food bias percentage in solver `{}`", spec)
+						})?;
+					Ok(Self::StaticHamiltonian {
+						food_bias_percent,
+						regenerate: false,
+					})
+				} else if let Some(steps) = spec.strip_prefix("dynamic-indirect-") {
+					dynamic(JitterKind::JitterWhenIndirect(parse_steps(steps)?))
+				} else if let Some(steps) = spec.strip_prefix("dynamic-always-") {
+					dynamic(JitterKind::JitterAlways(parse_steps(steps)?))
+				} else if let Some(value) = spec.strip_prefix("dynamic-longer-") {
+					let (percent, steps) = parse_threshold_and_steps(value)?;
+					dynamic(JitterKind::JitterWhenLonger { percent, steps })
+				} else if let Some(value) = spec.strip_prefix("dynamic-fill-") {
+					let (percent, steps) = parse_threshold_and_steps(value)?;
+					dynamic(JitterKind::JitterBelowFill { percent, steps })
+				} else if let Some(value) = spec.strip_prefix("dynamic-budget-") {
+					let (millis, steps) = parse_threshold_and_steps(value)?;
+					dynamic(JitterKind::JitterWithinBudget {
+						budget: Duration::from_millis(millis as u64),
+						steps,
+					})
+				} else {
+					Err(format!("Unknown solver `{}`", spec))
+				}
+			}
+		}
+	}
+
+	fn name(&self) -> String {
+		match *self {
+			Self::ZigZag => "zigzag".to_string(),
+			Self::StaticHamiltonian {
+				food_bias_percent,
+				regenerate,
+			} => {
+				let mut name = match food_bias_percent {
+					0 => "static".to_string(),
+					percent => format!("static-food-{}", percent),
+				};
+				if regenerate {
+					name.push_str("-regen");
+				}
+
+				name
+			}
+			Self::DynamicHamiltonian {
+				jitter,
+				growth,
+				incremental,
+				remove_detours,
+				endgame_fill,
+				step_costs,
+			} => {
+				let mut name = match jitter {
+					JitterKind::NoJitter => "dynamic".to_string(),
+					JitterKind::JitterWhenIndirect(steps) => format!("dynamic-indirect-{}", steps),
+					JitterKind::JitterAlways(steps) => format!("dynamic-always-{}", steps),
+					JitterKind::JitterWhenLonger { percent, steps } => {
+						format!("dynamic-longer-{}-{}", percent, steps)
+					}
+					JitterKind::JitterBelowFill { percent, steps } => {
+						format!("dynamic-fill-{}-{}", percent, steps)
+					}
+					JitterKind::JitterWithinBudget { budget, steps } => {
+						format!("dynamic-budget-{}-{}", budget.as_millis(), steps)
+					}
+				};
+
+				if let Some((growth_name, _)) = GROWTH_STRATEGIES.iter().find(|(_, g)| *g == growth)
+				{
+					write!(name, "-{}", growth_name).unwrap();
+				}
+				if incremental {
+					name.push_str("-incremental");
+				}
+				if remove_detours {
+					name.push_str("-no-detours");
+				}
+				if let Some(percent) = endgame_fill {
+					write!(name, "-endgame-{}", percent).unwrap();
+				}
+				if step_costs != StepCosts::default() {
+					write!(
+						name,
+						"-costs-{}-{}-{}",
+						step_costs.clockwise, step_costs.out, step_costs.turn
+					)
+					.unwrap();
+				}
+
+				name
+			}
+		}
+	}
+
+	/// Play a game for every seed, each with a fresh solver.
+	fn simulate(
+		&self,
+		config: SimulationConfig,
+		seeds: &[u64],
+		on_game_played: impl Fn(&SimulatedGame) + Sync,
+	) -> SimulationResults {
+		let seeds = seeds.iter().copied();
+		match *self {
+			Self::ZigZag => {
+				simulate_many_with_progress(config, seeds, || BasicSnakeSolver, on_game_played)
+			}
+			Self::StaticHamiltonian {
+				food_bias_percent,
+				regenerate,
+			} => simulate_many_with_progress(
+				config,
+				seeds,
+				|| {
+					RandomSpanningTreeSolver::new()
+						.with_food_bias(food_bias_percent as f32 / 100.0)
+						.with_regeneration(regenerate)
+				},
+				on_game_played,
+			),
+			Self::DynamicHamiltonian {
+				jitter,
+				growth,
+				incremental,
+				remove_detours,
+				endgame_fill,
+				step_costs,
+			} => simulate_many_with_progress(
+				config,
+				seeds,
+				|| {
+					let mut solver = SnakeSpanningTreeSolver::new(jitter)
+						.with_growth_strategy(growth)
+						.with_incremental(incremental)
+						.with_detour_removal(remove_detours)
+						.with_step_costs(step_costs);
+					if let Some(percent) = endgame_fill {
+						solver = solver.with_endgame(percent);
+					}
+
+					solver
+				},
+				on_game_played,
+			),
+		}
+	}
+}
+
+fn outcome_name(state: AutoPlayerState) -> &'static str {
+	match state {
+		AutoPlayerState::Playing => unreachable!("Simulated games always end"),
+		AutoPlayerState::Finished => "finished",
+		AutoPlayerState::Killed => "killed",
+		AutoPlayerState::Stalled => "stalled",
+	}
+}
+
+struct GameRecord {
+	solver: String,
+	size: usize,
+	game: SimulatedGame,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+	Json,
+	Csv,
+}
+
+struct Args {
+	solvers: Vec<SolverSpec>,
+	sizes: Vec<usize>,
+	seeds: Vec<u64>,
+	threads: usize,
+	max_steps: Option<u64>,
+	max_steps_without_food: Option<u64>,
+	format: Format,
+	output: Option<String>,
+}
+
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+	value
+		.split(',')
+		.map(str::trim)
+		.filter(|item| !item.is_empty())
+		.map(parse)
+		.collect()
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+	value
+		.parse::<T>()
+		.map_err(|_| format!("Invalid number `{}`", value))
+}
+
+fn parse_seeds(value: &str) -> Result<Vec<u64>, String> {
+	if let Some((start, end)) = value.split_once("..") {
+		let start = parse_number::<u64>(start)?;
+		let end = parse_number::<u64>(end)?;
+		Ok((start..end).collect())
+	} else {
+		parse_list(value, parse_number)
+	}
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = Args {
+		solvers: parse_list(
+			"zigzag,static,dynamic,dynamic-indirect-10,dynamic-indirect-1",
+			SolverSpec::parse,
+		)?,
+		sizes: vec![10, 20, 40],
+		seeds: (0..10).collect(),
+		threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+		max_steps: None,
+		max_steps_without_food: None,
+		format: Format::Json,
+		output: None,
+	};
+
+	let mut iter = std::env::args().skip(1);
+	while let Some(flag) = iter.next() {
+		if flag == "-h" || flag == "--help" {
+			print!("{}", USAGE);
+			std::process::exit(0);
+		}
+
+		let value = iter
+			.next()
+			.ok_or_else(|| format!("Missing value for `{}`", flag))?;
+
+		match flag.as_str() {
+			"--solvers" => args.solvers = parse_list(&value, SolverSpec::parse)?,
+			"--sizes" => args.sizes = parse_list(&value, parse_number)?,
+			"--seeds" => args.seeds = parse_seeds(&value)?,
+			"--threads" => args.threads = parse_number::<usize>(&value)?.max(1),
+			"--max-steps" => args.max_steps = Some(parse_number(&value)?),
+			"--max-steps-without-food" => args.max_steps_without_food = Some(parse_number(&value)?),
+			"--format" => {
+				args.format = match value.as_str() {
+					"json" => Format::Json,
+					"csv" => Format::Csv,
+					_ => return Err(format!("Unknown format `{}`", value)),
+				}
+			}
+			"--output" => args.output = Some(value),
+			_ => return Err(format!("Unknown argument `{}`", flag)),
+		}
+	}
+
+	if let Some(size) = args.sizes.iter().find(|&&size| size < 4 || size % 2 != 0) {
+		return Err(format!(
+			"World sizes must be even and at least 4, got {}",
+			size
+		));
+	}
+
+	Ok(args)
+}
+
+fn json_string(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len() + 2);
+	escaped.push('"');
+	for c in value.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			c => escaped.push(c),
+		}
+	}
+	escaped.push('"');
+	escaped
+}
+
+fn write_csv(records: &[GameRecord]) -> String {
+	let mut out = String::new();
+	out.push_str(
+		"solver,size,seed,outcome,steps,paths,solver_time_ns,mean_path_time_ns,max_path_time_ns,min_path_len,mean_path_len,max_path_len\n",
+	);
+
+	for r in records {
+		let stats = &r.game.stats;
+		writeln!(
+			out,
+			"{},{},{},{},{},{},{},{},{},{},{},{}",
+			r.solver,
+			r.size,
+			r.game.seed,
+			outcome_name(r.game.state),
+			stats.total_steps,
+			stats.path_computations,
+			stats.solver_time.as_nanos(),
+			stats.mean_path_time().as_nanos(),
+			stats.max_path_time.as_nanos(),
+			stats.min_path_length().unwrap_or(0),
+			stats.mean_path_length(),
+			stats.max_path_length().unwrap_or(0)
+		)
+		.unwrap();
+	}
+
+	out
+}
+
+fn write_json(records: &[GameRecord], args: &Args) -> String {
+	let mut out = String::new();
+	out.push_str("{\n  \"games\": [\n");
+
+	for (i, r) in records.iter().enumerate() {
+		let stats = &r.game.stats;
+		write!(
+			out,
+			"    {{\"solver\": {}, \"size\": {}, \"seed\": {}, \"outcome\": {}, \"steps\": {}, \"paths\": {}, \
+			\"solver_time_ns\": {}, \"mean_path_time_ns\": {}, \"max_path_time_ns\": {}, \
+			\"min_path_len\": {}, \"mean_path_len\": {}, \"max_path_len\": {}}}",
+			json_string(&r.solver),
+			r.size,
+			r.game.seed,
+			json_string(outcome_name(r.game.state)),
+			stats.total_steps,
+			stats.path_computations,
+			stats.solver_time.as_nanos(),
+			stats.mean_path_time().as_nanos(),
+			stats.max_path_time.as_nanos(),
+			stats.min_path_length().unwrap_or(0),
+			stats.mean_path_length(),
+			stats.max_path_length().unwrap_or(0)
+		)
+		.unwrap();
+		out.push_str(if i + 1 < records.len() { ",\n" } else { "\n" });
+	}
+
+	out.push_str("  ],\n  \"summary\": [\n");
+
+	// Summarise each solver and size combination, in the order they were requested
+	let mut groups = Vec::new();
+	for solver in args.solvers.iter() {
+		for &size in args.sizes.iter() {
+			groups.push((solver.name(), size));
+		}
+	}
+
+	for (i, (solver, size)) in groups.iter().enumerate() {
+		let games = records
+			.iter()
+			.filter(|r| &r.solver == solver && r.size == *size)
+			.collect::<Vec<_>>();
+
+		let count_outcome =
+			|state: AutoPlayerState| games.iter().filter(|r| r.game.state == state).count();
+		let finished_steps = games
+			.iter()
+			.filter(|r| r.game.state == AutoPlayerState::Finished)
+			.map(|r| r.game.stats.total_steps)
+			.collect::<Vec<_>>();
+		let total_paths = games
+			.iter()
+			.map(|r| r.game.stats.path_computations)
+			.sum::<u64>();
+		let total_time = games
+			.iter()
+			.map(|r| r.game.stats.solver_time)
+			.sum::<Duration>();
+
+		write!(
+			out,
+			"    {{\"solver\": {}, \"size\": {}, \"games\": {}, \"finished\": {}, \"killed\": {}, \"stalled\": {}, \
+			\"min_steps\": {}, \"mean_steps\": {}, \"max_steps\": {}, \"mean_path_time_ns\": {}}}",
+			json_string(solver),
+			size,
+			games.len(),
+			count_outcome(AutoPlayerState::Finished),
+			count_outcome(AutoPlayerState::Killed),
+			count_outcome(AutoPlayerState::Stalled),
+			finished_steps.iter().min().copied().unwrap_or(0),
+			finished_steps.iter().sum::<u64>() as f64 / finished_steps.len().max(1) as f64,
+			finished_steps.iter().max().copied().unwrap_or(0),
+			total_time.as_nanos() / total_paths.max(1) as u128
+		)
+		.unwrap();
+		out.push_str(if i + 1 < groups.len() { ",\n" } else { "\n" });
+	}
+
+	out.push_str("  ]\n}\n");
+	out
+}
+
+fn main() {
+	let args = match parse_args() {
+		Ok(args) => args,
+		Err(err) => {
+			eprintln!("error: {}\n\n{}", err, USAGE);
+			std::process::exit(2);
+		}
+	};
+
+	let total_games = args.solvers.len() * args.sizes.len() * args.seeds.len();
+	eprintln!("Running {} games", total_games);
+
+	let finished_games = AtomicUsize::new(0);
+	let mut records = Vec::with_capacity(total_games);
+	for solver in args.solvers.iter() {
+		let name = solver.name();
+		for &size in args.sizes.iter() {
+			let mut config = SimulationConfig::new(size).with_threads(args.threads);
+			if let Some(max_steps) = args.max_steps {
+				config = config.with_max_steps(max_steps);
+			}
+			if let Some(max_steps) = args.max_steps_without_food {
+				config = config.with_max_steps_without_food(max_steps);
+			}
+
+			let results = solver.simulate(config, &args.seeds, |game| {
+				if game.state != AutoPlayerState::Finished {
+					eprintln!(
+						"{} on size {} with seed {} was {}",
+						name,
+						size,
+						game.seed,
+						outcome_name(game.state)
+					);
+				}
+
+				let done = finished_games.fetch_add(1, Ordering::Relaxed) + 1;
+				eprint!("\r{}/{} games done", done, total_games);
+			});
+
+			records.extend(results.games.into_iter().map(|game| GameRecord {
+				solver: name.clone(),
+				size,
+				game,
+			}));
+		}
+	}
+	eprintln!();
+
+	let output = match args.format {
+		Format::Json => write_json(&records, &args),
+		Format::Csv => write_csv(&records),
+	};
+
+	match &args.output {
+		Some(path) => std::fs::write(path, output).expect("Failed to write the output file"),
+		None => std::io::stdout()
+			.write_all(output.as_bytes())
+			.expect("Failed to write to stdout"),
+	}
+}
@@ -0,0 +1,83 @@
+use crate::{array2d::Array2D, auto::Path, Coord};
+
+use super::{Cell, SnakeWorld};
+
+/// Where the snake's body will be as it follows a path, see `SnakeWorld::forecast_occupancy`.
+#[derive(Clone)]
+pub struct OccupancyForecast {
+	free_after: Array2D<u32>,
+	safe_steps: usize,
+	path_len: usize,
+}
+
+impl OccupancyForecast {
+	/// The number of steps after which each cell is empty, so the head can move into a cell with
+	/// the value `t` on step `t + 1` or later. Cells that are already empty are `0`.
+	pub fn free_after_grid(&self) -> &Array2D<u32> {
+		&self.free_after
+	}
+
+	/// The number of steps after which `coord` is empty, or `None` if it's out of bounds.
+	pub fn free_after(&self, coord: Coord) -> Option<u32> {
+		self.free_after.get(coord).copied()
+	}
+
+	/// Whether the head could move into `coord` on step `step`, counting from 1.
+	pub fn is_free_at(&self, coord: Coord, step: u32) -> bool {
+		self.free_after(coord)
+			.is_some_and(|free_after| free_after < step)
+	}
+
+	/// How many steps of the path can be taken before the snake would leave the world or run into
+	/// itself. The forecast stops at that step.
+	pub fn safe_steps(&self) -> usize {
+		self.safe_steps
+	}
+
+	/// Whether the whole path can be followed without the snake dying.
+	pub fn is_path_safe(&self) -> bool {
+		self.safe_steps == self.path_len
+	}
+}
+
+impl SnakeWorld {
+	/// Project the snake forward along `path`, returning when every cell will be empty again. Only
+	/// the current food is accounted for, as the food that spawns after it can't be known ahead of time.
+	pub fn forecast_occupancy(&self, path: &Path) -> OccupancyForecast {
+		// A cell with the value `n` is culled after `n + 1` more steps
+		let mut free_after = Array2D::new(self.size(), 0);
+		for coord in self.cells.iter_all_coords() {
+			if let Some(&Cell::Snake(value)) = self.cells.get(coord) {
+				free_after.set(coord, value + 1);
+			}
+		}
+
+		let mut forecast = OccupancyForecast {
+			free_after,
+			safe_steps: 0,
+			path_len: path.len(),
+		};
+
+		let mut length = self.snake_length;
+		let mut food = Some(self.food_coord);
+		let mut head = self.head_coord;
+		for (index, dir) in path.iter_directions().enumerate() {
+			let step = index as u32 + 1;
+			head = head.go_towards(dir);
+			if !forecast.is_free_at(head, step) {
+				break;
+			}
+
+			if food == Some(head) {
+				length += 3;
+				food = None;
+			}
+
+			// The new head is given the snake's length and culled along with the rest of the body
+			forecast.free_after.set(head, step + length);
+			forecast.safe_steps += 1;
+		}
+
+		forecast
+	}
+}
@@ -5,6 +5,9 @@ use crate::array2d::Array2D;
 use crate::direction::Direction;
 use crate::{auto::Path, Coord, Offset};
 
+mod forecast;
+pub use forecast::*;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Cell {
 	Empty,
@@ -19,6 +22,7 @@ pub enum SnakeResult {
 	Finished,
 }
 
+#[derive(Clone)]
 pub struct SnakeWorld {
 	snake_length: u32,
 	head_coord: Coord,
@@ -104,7 +108,7 @@ impl SnakeWorld {
 	fn find_random_valid_food_coord(&self) -> Option<Coord> {
 		if (self.snake_length as usize) < self.cells.count() * 7 / 8 {
 			// If more than an eighth of the grid is empty, randomly probe until empty cell found
-			let mut rng = rand::thread_rng();
+			let mut rng = crate::rng::thread_rng();
 			let mut coord = Coord::new(
 				rng.gen_range(0..self.cells.size()),
 				rng.gen_range(0..self.cells.size()),
@@ -130,7 +134,7 @@ impl SnakeWorld {
 			if cells.is_empty() {
 				None
 			} else {
-				let mut rng = rand::thread_rng();
+				let mut rng = crate::rng::thread_rng();
 				Some(cells[rng.gen_range(0..cells.len())])
 			}
 		}
@@ -181,7 +185,7 @@ impl SnakeWorld {
 		}
 
 		let mut next_snake_cell_data: Option<NextSnakeCellData> = None;
-		let Some(Cell::Snake( mut  current_value)) = self.cells.get(pos) else {
+		let Some(Cell::Snake(mut current_value)) = self.cells.get(pos) else {
 			unreachable!("Head coord is not a snake cell")
 		};
 
@@ -0,0 +1,183 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{array2d::Array2D, direction::Direction, grid_graph::GridGraph, path::Path, Coord};
+
+use super::utils::next_dir_on_collision_grid;
+
+/// The index given to cells that the cycle doesn't visit.
+const UNVISITED: u32 = u32::MAX;
+
+/// The first reason a traced cycle isn't a Hamiltonian cycle of the world.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CycleViolation {
+	/// The cycle doesn't have exactly one cell for every cell of the world.
+	WrongLength { expected: usize, actual: usize },
+	/// The cycle leaves the world.
+	OutOfBounds(Coord),
+	/// The cycle passes over a cell more than once.
+	RevisitedCell(Coord),
+	/// Two consecutive cells aren't neighbours, including the last and first cells.
+	NotAdjacent(Coord, Coord),
+}
+
+impl Display for CycleViolation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			CycleViolation::WrongLength { expected, actual } => {
+				write!(f, "Cycle has {} cells, expected {}", actual, expected)
+			}
+			CycleViolation::OutOfBounds(coord) => {
+				write!(f, "Cycle leaves the world at {:?}", coord)
+			}
+			CycleViolation::RevisitedCell(coord) => {
+				write!(f, "Cycle visits {:?} more than once", coord)
+			}
+			CycleViolation::NotAdjacent(from, to) => {
+				write!(f, "Cycle jumps from {:?} to {:?}", from, to)
+			}
+		}
+	}
+}
+
+/// A loop over the cells of the world, in the order the snake follows it. Cycles traced from a
+/// solver aren't guaranteed to be Hamiltonian, so check them with `verify` before relying on it.
+#[derive(Clone)]
+pub struct HamiltonianCycle {
+	cells: Vec<Coord>,
+	indices: Array2D<u32>,
+}
+
+impl HamiltonianCycle {
+	/// Build a cycle from its cells in order. The last cell is expected to lead back to the first.
+	pub fn from_cells(world_size: usize, cells: Vec<Coord>) -> Self {
+		let mut indices = Array2D::new(world_size, UNVISITED);
+		for (index, &coord) in cells.iter().enumerate() {
+			// Keep the first index of cells that are visited more than once
+			if let Some(value) = indices.get_mut(coord) {
+				if *value == UNVISITED {
+					*value = index as u32;
+				}
+			}
+		}
+
+		Self { cells, indices }
+	}
+
+	/// Follow `next_dir` from `start` until it returns to `start`. The walk is cut off once it has
+	/// more cells than the world, so cycles that don't close fail verification.
+	pub fn trace(
+		world_size: usize,
+		start: Coord,
+		mut next_dir: impl FnMut(Coord) -> Direction,
+	) -> Self {
+		let cell_count = world_size * world_size;
+
+		let mut cells = vec![start];
+		let mut current = start;
+		while cells.len() <= cell_count {
+			current = current.go_towards(next_dir(current));
+			if current == start {
+				break;
+			}
+			cells.push(current);
+		}
+
+		Self::from_cells(world_size, cells)
+	}
+
+	/// Trace the cycle around the walls of a boolean collision grid, as used by `RandomSpanningTreeSolver`.
+	pub fn from_collision_grid(grid: &GridGraph<bool>, start: Coord) -> Self {
+		Self::trace(grid.size(), start, |coord| {
+			next_dir_on_collision_grid(grid, coord)
+		})
+	}
+
+	/// The cells of the cycle in order, starting from the cell it was traced from.
+	pub fn cells(&self) -> &[Coord] {
+		&self.cells
+	}
+
+	/// The position of every cell in `cells`, or `u32::MAX` for cells the cycle doesn't visit.
+	pub fn indices(&self) -> &Array2D<u32> {
+		&self.indices
+	}
+
+	pub fn len(&self) -> usize {
+		self.cells.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.cells.is_empty()
+	}
+
+	/// The position of a cell in the cycle, if the cycle visits it.
+	pub fn index_of(&self, coord: Coord) -> Option<u32> {
+		self.indices
+			.get(coord)
+			.copied()
+			.filter(|&index| index != UNVISITED)
+	}
+
+	/// The cell after `coord` on the cycle.
+	pub fn next_coord(&self, coord: Coord) -> Option<Coord> {
+		let index = self.index_of(coord)? as usize;
+		Some(self.cells[(index + 1) % self.cells.len()])
+	}
+
+	/// The cell before `coord` on the cycle.
+	pub fn previous_coord(&self, coord: Coord) -> Option<Coord> {
+		let index = self.index_of(coord)? as usize;
+		Some(self.cells[(index + self.cells.len() - 1) % self.cells.len()])
+	}
+
+	/// The path that follows the cycle from `from` to `to`, or `None` if either cell isn't on
+	/// the cycle or the cycle jumps between cells that aren't neighbours along the way.
+	pub fn path_between(&self, from: Coord, to: Coord) -> Option<Path> {
+		let start = self.index_of(from)? as usize;
+		let steps = self.distance(from, to)? as usize;
+
+		let mut path = Path::new();
+		for index in start..start + steps {
+			let current = self.cells[index % self.cells.len()];
+			let next = self.cells[(index + 1) % self.cells.len()];
+			path.push(Direction::each().find(|&dir| current.go_towards(dir) == next)?);
+		}
+
+		Some(path)
+	}
+
+	/// How many steps it takes to get from `from` to `to` by following the cycle.
+	pub fn distance(&self, from: Coord, to: Coord) -> Option<u32> {
+		let from = self.index_of(from)?;
+		let to = self.index_of(to)?;
+		let len = self.cells.len() as u32;
+		Some((to + len - from) % len)
+	}
+
+	/// Check that the cycle visits every cell of the world exactly once and is closed.
+	pub fn verify(&self) -> Result<(), CycleViolation> {
+		let expected = self.indices.count();
+		if self.cells.len() != expected {
+			return Err(CycleViolation::WrongLength {
+				expected,
+				actual: self.cells.len(),
+			});
+		}
+
+		let mut visited = Array2D::new(self.indices.size(), false);
+		for (index, &coord) in self.cells.iter().enumerate() {
+			match visited.get(coord) {
+				None => return Err(CycleViolation::OutOfBounds(coord)),
+				Some(true) => return Err(CycleViolation::RevisitedCell(coord)),
+				Some(false) => visited.set(coord, true),
+			}
+
+			let next = self.cells[(index + 1) % self.cells.len()];
+			if !Direction::each().any(|dir| coord.go_towards(dir) == next) {
+				return Err(CycleViolation::NotAdjacent(coord, next));
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -1,12 +1,28 @@
 use crate::{auto::Path, snake::SnakeWorld, ui::SnakeWorldViewer};
 
+use self::hamiltonian_cycle::HamiltonianCycle;
+
 pub mod basic;
+pub mod hamiltonian_cycle;
 pub mod random_spanning_tree;
 pub mod snake_spanning_tree;
 mod utils;
+pub mod validating;
 
 pub trait SnakeSolver {
 	fn get_next_path(&mut self, world: &SnakeWorld) -> Path;
 
+	/// Whether the last path returned by `get_next_path` couldn't follow the route that was
+	/// planned to the food, because the spanning tree had to be grown over it.
+	fn last_path_was_overridden(&self) -> bool {
+		false
+	}
+
+	/// The whole cycle the last path was taken from, starting at the snake's head, for solvers
+	/// that follow one. The cycle isn't checked, see `HamiltonianCycle::verify`.
+	fn hamiltonian_cycle(&self, _world: &SnakeWorld) -> Option<HamiltonianCycle> {
+		None
+	}
+
 	fn decorate_widget<'a>(&'a self, widget: SnakeWorldViewer<'a>) -> SnakeWorldViewer<'a>;
 }
@@ -1,16 +1,56 @@
-extern crate rand;
+use std::{cmp::Reverse, collections::BinaryHeap};
 
-use crate::{auto::Path, direction::Direction, grid_graph::GridGraph, ui::SnakeWorldViewer, Coord};
+use rand::Rng;
 
-use super::{utils::build_path_from_collision_grid, SnakeSolver};
+use crate::{
+	array2d::Array2D, auto::Path, direction::Direction, grid_graph::GridGraph,
+	ui::SnakeWorldViewer, Coord,
+};
+
+use super::{
+	hamiltonian_cycle::HamiltonianCycle,
+	utils::{
+		body_follows_cycle, build_path_from_collision_grid, get_valid_dirs_from_coord,
+		next_dir_on_collision_grid,
+	},
+	SnakeSolver,
+};
 
 pub struct RandomSpanningTreeSolver {
 	pub prev_grid: Option<GridGraph<bool>>,
+	food_bias: f32,
+	regenerate: bool,
+}
+
+impl Default for RandomSpanningTreeSolver {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl RandomSpanningTreeSolver {
 	pub fn new() -> Self {
-		Self { prev_grid: None }
+		Self {
+			prev_grid: None,
+			food_bias: 0.0,
+			regenerate: false,
+		}
+	}
+
+	/// Bias the tree's random edge weights by their distance from the line between the snake's
+	/// head and the food, from `0.0` for uniformly random weights to `1.0` for weights based only
+	/// on that distance, so that the edges near the line join the tree first.
+	pub fn with_food_bias(mut self, food_bias: f32) -> Self {
+		self.food_bias = food_bias.clamp(0.0, 1.0);
+		self
+	}
+
+	/// Generate a new cycle around the snake's body for every food instead of keeping the first
+	/// one, and switch to it if it reaches the food sooner. The new tree keeps the walls that the
+	/// body follows, so the body already lies along the new cycle and following it is safe.
+	pub fn with_regeneration(mut self, regenerate: bool) -> Self {
+		self.regenerate = regenerate;
+		self
 	}
 }
 
@@ -25,15 +65,52 @@ impl SnakeSolver for RandomSpanningTreeSolver {
 		// Generate the graph over every second grid square with minimum weights
 		// Then convert those edges into a MST
 		// And convert that MST into a collision space
-		let grid = match self.prev_grid.take() {
-			None => generate_grid_network(world, generate_edges(world)),
-			Some(grid) => grid,
+		let (grid, path) = match self.prev_grid.take() {
+			None => {
+				let grid =
+					generate_grid_network(world, generate_edges(world, self.food_bias, None));
+				let path = build_path_from_collision_grid(&grid, world);
+				(grid, path)
+			}
+			Some(grid) if self.regenerate => {
+				let path = build_path_from_collision_grid(&grid, world);
+
+				// Keep the tree edges the snake's body walks around, and avoid the ones it crosses
+				let constraints = trace_body_constraints(world);
+				let edges = generate_edges(world, self.food_bias, Some(&constraints));
+				let new_grid = generate_constrained_grid_network(world, &edges);
+
+				// Only switch to the new cycle if it's safe and reaches the food sooner
+				if body_follows_cycle(world, |coord| {
+					Some(coord.go_towards(next_dir_on_collision_grid(&new_grid, coord)))
+				}) {
+					let new_path = build_path_from_collision_grid(&new_grid, world);
+					if new_path.len() < path.len() {
+						(new_grid, new_path)
+					} else {
+						(grid, path)
+					}
+				} else {
+					(grid, path)
+				}
+			}
+			Some(grid) => {
+				let path = build_path_from_collision_grid(&grid, world);
+				(grid, path)
+			}
 		};
 
-		let path = build_path_from_collision_grid(&grid, world);
 		self.prev_grid = Some(grid);
 
-		return path;
+		path
+	}
+
+	fn hamiltonian_cycle(&self, world: &crate::snake::SnakeWorld) -> Option<HamiltonianCycle> {
+		let grid = self.prev_grid.as_ref()?;
+		Some(HamiltonianCycle::from_collision_grid(
+			grid,
+			world.snake_head_coord(),
+		))
 	}
 
 	// UI code for drawing the collision grid
@@ -49,9 +126,66 @@ impl SnakeSolver for RandomSpanningTreeSolver {
 	}
 }
 
-// Generate all of the random edges for a given world
-fn generate_edges(world: &crate::snake::SnakeWorld) -> Vec<Edge> {
-	let _food = world.food_coord();
+// Find the tree edges that the snake's body needs for the cycle to follow it. Edges are indexed
+// by node, where node `n` is the graph node at `2n + 1`, and are `true` if the edge must be in
+// the tree and `false` if it must not be.
+fn trace_body_constraints(world: &crate::snake::SnakeWorld) -> GridGraph<Option<bool>> {
+	let mut constraints = GridGraph::new(world.size() / 2, None);
+
+	let mut current = world.snake_head_coord();
+	for dir in world.calculate_snake_path_from_head().iter_directions() {
+		let prev = current.go_towards(dir);
+		let [clockwise, out] = get_valid_dirs_from_coord(prev);
+
+		// The wall between a cell and its clockwise neighbour runs from the centre of the cell's
+		// node, and the cycle only steps out of the node if that wall is there
+		let node = prev.map_values(|v| v / 2);
+		constraints.try_set_edge(node, clockwise.rotate_left(), Some(dir.opposite() == out));
+
+		current = prev;
+	}
+
+	constraints
+}
+
+// Generate all of the random edges for a given world, with weights biased towards the line
+// from the snake's head to the food by `food_bias`. Edges required by `constraints` are sorted
+// first, and edges it rules out are left out.
+fn generate_edges(
+	world: &crate::snake::SnakeWorld,
+	food_bias: f32,
+	constraints: Option<&GridGraph<Option<bool>>>,
+) -> Vec<Edge> {
+	let head = world.snake_head_coord();
+	let food = world.food_coord();
+
+	let edge_weight = |a: Coord, b: Coord| {
+		let constraint = constraints.and_then(|constraints| {
+			let dir = if a.x == b.x {
+				Direction::Down
+			} else {
+				Direction::Right
+			};
+			*constraints.get_edge(a.map_values(|v| v / 2), dir)?
+		});
+		match constraint {
+			Some(true) => return Some(-1.0),
+			Some(false) => return None,
+			None => {}
+		}
+
+		let random = crate::rng::thread_rng().gen::<f32>();
+		if food_bias == 0.0 {
+			return Some(random);
+		}
+
+		let distance = distance_to_segment(
+			((a.x + b.x) as f32 / 2.0, (a.y + b.y) as f32 / 2.0),
+			(head.x as f32, head.y as f32),
+			(food.x as f32, food.y as f32),
+		);
+		Some(random * (1.0 - food_bias) + distance / world.size() as f32 * food_bias)
+	};
 
 	// Create a random directed graph of edges
 	// Connecting every second square to it's direct neighbour (not diagonally)
@@ -62,11 +196,9 @@ fn generate_edges(world: &crate::snake::SnakeWorld) -> Vec<Edge> {
 			for (off_x, off_y) in [(0, 1), (1, 0)] {
 				let b = Coord::new(x + off_x * 2, y + off_y * 2);
 
-				edges.push(Edge {
-					a,
-					b,
-					weight: rand::random::<f32>(),
-				});
+				if let Some(weight) = edge_weight(a, b) {
+					edges.push(Edge { a, b, weight });
+				}
 			}
 		}
 	}
@@ -74,112 +206,164 @@ fn generate_edges(world: &crate::snake::SnakeWorld) -> Vec<Edge> {
 	// Add the missing edges to the bottom right node
 	// Because the above loop omitted them for simplicity
 	let size = world.size();
-	edges.push(Edge {
-		a: Coord::new(size - 1, size - 3),
-		b: Coord::new(size - 1, size - 1),
-		weight: rand::random::<f32>(),
-	});
-	edges.push(Edge {
-		a: Coord::new(size - 3, size - 1),
-		b: Coord::new(size - 1, size - 1),
-		weight: rand::random::<f32>(),
-	});
+	for a in [
+		Coord::new(size - 1, size - 3),
+		Coord::new(size - 3, size - 1),
+	] {
+		let b = Coord::new(size - 1, size - 1);
+		if let Some(weight) = edge_weight(a, b) {
+			edges.push(Edge { a, b, weight });
+		}
+	}
 
 	// Sort the edges by weight for the later MST calculations
 	edges.sort_by(|a, b| a.weight.partial_cmp(&b.weight).unwrap());
 
-	return edges;
+	edges
 }
 
-fn generate_grid_network(
-	world: &crate::snake::SnakeWorld,
-	mut edges: Vec<Edge>,
-) -> GridGraph<bool> {
+// The distance from a point to the line segment between `start` and `end`
+fn distance_to_segment(point: (f32, f32), start: (f32, f32), end: (f32, f32)) -> f32 {
+	let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+	let length_squared = dx * dx + dy * dy;
+
+	let t = if length_squared == 0.0 {
+		0.0
+	} else {
+		(((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_squared).clamp(0.0, 1.0)
+	};
+
+	let (x, y) = (start.0 + t * dx, start.1 + t * dy);
+	((point.0 - x).powi(2) + (point.1 - y).powi(2)).sqrt()
+}
+
+fn generate_grid_network(world: &crate::snake::SnakeWorld, edges: Vec<Edge>) -> GridGraph<bool> {
 	let food = world.food_coord();
 
-	// Generate the minimum spanning tree from edges
+	// Generate the spanning tree from edges
 	//   Instead of generating the tree structure completely
 	//   It is only partially generated as it will be converted into a collision GridGraph anyway
-	//   Thus we only store the necessary information to continue building a valid MST
+	//   Thus we only store the necessary information to continue building a valid tree
 	//     rather than enough to store it
-	let mut visited = Vec::<Coord>::new();
-	let mut grid = GridGraph::<bool>::new(world.size() as usize, false);
+	//
+	// The tree is grown by sweeping over the sorted edges in passes, adding every edge that connects
+	// the tree to a new node at the moment it's reached in the sweep, until a pass adds nothing.
+	// Rather than sweeping, each edge is given the time the sweep would next reach it once one of
+	// its nodes joins the tree, and edges are processed in that order from a heap.
+	let mut visited = Array2D::new(world.size(), false);
+	let mut grid = GridGraph::<bool>::new(world.size(), false);
+
+	// Index the edges touching each node
+	let mut node_edges = Array2D::new(world.size(), Vec::new());
+	for (i, edge) in edges.iter().enumerate() {
+		node_edges[edge.a].push(i);
+		node_edges[edge.b].push(i);
+	}
+
+	// The time the sweep reaches an edge after the given time, where each pass spans `edges.len()`
+	let edge_count = edges.len();
+	let next_sweep_time = |time: usize, index: usize| {
+		let pass = time / edge_count;
+		if pass * edge_count + index > time {
+			pass * edge_count + index
+		} else {
+			(pass + 1) * edge_count + index
+		}
+	};
+
+	let mut queue = BinaryHeap::new();
+	let mut processed = vec![false; edges.len()];
 
-	// Mark the start point for the spanning tree
+	// Mark the start point for the spanning tree, which is visited before the first pass begins
 	let start = Coord::new(food.x - (food.x % 2) + 1, food.y - (food.y % 2) + 1);
-	visited.push(start);
-
-	// has not reached all vertexes
-	// & has not ran out of possible connections
-	let mut updated = true;
-	while updated {
-		updated = false;
-
-		let mut i = 0;
-		while i < edges.len() {
-			let edge = &edges[i];
-			let has_a = visited.contains(&edge.a);
-			let has_b = visited.contains(&edge.b);
-
-			// If the connection between these two nodes already exists
-			// Remove this option as a shorter path has already been taken
-			if has_a && has_b {
-				edges.remove(i);
-				updated = true;
-				continue;
-			}
+	visited[start] = true;
+	for &i in node_edges[start].iter() {
+		queue.push(Reverse((i, i)));
+	}
 
-			// If this connection is new, and plausible from the current tree
-			// add this edge
-			if has_a || has_b {
-				// Remove the edge which is about to be added
-				let mut wall = edges.remove(i);
-
-				// Push the new node as visited
-				if !has_a {
-					visited.push(wall.a);
-				} else if !has_b {
-					visited.push(wall.b);
-				}
+	while let Some(Reverse((time, i))) = queue.pop() {
+		if processed[i] {
+			continue;
+		}
+		processed[i] = true;
 
-				// Swap the edge direction to point existing -> new
-				//   as the graph is non-directed
-				if !has_a && has_b {
-					let t = wall.a;
-					wall.a = wall.b;
-					wall.b = t;
-				}
+		// If both nodes are already in the tree, this edge would make a loop so it's skipped
+		let edge = &edges[i];
+		let new_node = match (visited[edge.a], visited[edge.b]) {
+			(true, false) => edge.b,
+			(false, true) => edge.a,
+			_ => continue,
+		};
 
-				// Convert from the graph coordinates to grid coordinates
-				let vertical = wall.a.y != wall.b.y;
-				let mut pos =
-					Coord::new(i32::min(wall.a.x, wall.b.x), i32::min(wall.a.y, wall.b.y));
-				if vertical {
-					pos.x -= 1;
-				} else {
-					pos.y -= 1;
-				}
-				// Update the collision grid to handle this edge
-				set_grid_edge(&mut grid, pos, vertical);
+		// Add the edge to the tree, and schedule the new node's edges
+		visited[new_node] = true;
+		add_tree_edge(&mut grid, edge);
 
-				// As each graph node spans two grid points
-				//  The second half of the edge needs to be added
-				if vertical {
-					pos.y += 1;
-				} else {
-					pos.x += 1;
-				}
-				set_grid_edge(&mut grid, pos, vertical);
+		for &j in node_edges[new_node].iter() {
+			if !processed[j] {
+				queue.push(Reverse((next_sweep_time(time, j), j)));
+			}
+		}
+	}
+
+	grid
+}
+
+// Join the edges into a tree in weight order, skipping any that would make a loop (Kruskal's
+// algorithm). Unlike the sweep in `generate_grid_network`, every edge required by the snake's
+// body is kept, as they're sorted first and can't make a loop between themselves.
+fn generate_constrained_grid_network(
+	world: &crate::snake::SnakeWorld,
+	edges: &[Edge],
+) -> GridGraph<bool> {
+	let mut grid = GridGraph::<bool>::new(world.size(), false);
 
-				updated = true;
-				continue;
+	// Each node points towards the root of its component
+	let mut parents = Array2D::new(world.size(), None);
+	fn find_root(parents: &mut Array2D<Option<Coord>>, mut coord: Coord) -> Coord {
+		while let Some(parent) = parents[coord] {
+			// Point the node at its grandparent on the way, to keep the paths short
+			if let Some(grandparent) = parents[parent] {
+				parents[coord] = Some(grandparent);
 			}
+			coord = parent;
+		}
+		coord
+	}
 
-			i += 1;
+	for edge in edges {
+		let root_a = find_root(&mut parents, edge.a);
+		let root_b = find_root(&mut parents, edge.b);
+		if root_a != root_b {
+			parents[root_a] = Some(root_b);
+			add_tree_edge(&mut grid, edge);
 		}
 	}
 
-	return grid;
+	grid
+}
+
+// Convert a tree edge between two graph nodes into collision grid edges
+fn add_tree_edge(grid: &mut GridGraph<bool>, edge: &Edge) {
+	// Convert from the graph coordinates to grid coordinates
+	let vertical = edge.a.y != edge.b.y;
+	let mut pos = Coord::new(i32::min(edge.a.x, edge.b.x), i32::min(edge.a.y, edge.b.y));
+	if vertical {
+		pos.x -= 1;
+	} else {
+		pos.y -= 1;
+	}
+	// Update the collision grid to handle this edge
+	set_grid_edge(grid, pos, vertical);
+
+	// As each graph node spans two grid points
+	//  The second half of the edge needs to be added
+	if vertical {
+		pos.y += 1;
+	} else {
+		pos.x += 1;
+	}
+	set_grid_edge(grid, pos, vertical);
 }
 
 fn set_grid_edge(grid: &mut GridGraph<bool>, pos: Coord, vertical: bool) {
@@ -1,28 +1,78 @@
-use std::ops::Deref;
+use std::{
+	ops::Deref,
+	time::{Duration, Instant},
+};
 
 use crate::{auto::Path, snake::SnakeWorld, ui::SnakeWorldViewer};
 
 use self::{
-	pathfinding::{PathfindingGrid, SnakePathfindResult},
+	incremental::IncrementalState,
+	pathfinding::{PathfindingGrid, SnakePathfindResult, StepCosts},
 	spanning_tree::{SnakeGrowResult, SpanningTree},
 };
 
-use super::SnakeSolver;
+use super::{hamiltonian_cycle::HamiltonianCycle, utils::body_follows_cycle, SnakeSolver};
 
-mod coordinates;
-mod pathfinding;
-mod spanning_tree;
+pub mod coordinates;
+mod incremental;
+pub mod pathfinding;
+pub mod spanning_tree;
 
 pub struct SnakeSpanningTreeSolver {
 	spanning_tree: Option<SpanningTree>,
 	pathfinding_grid: Option<PathfindingGrid>,
 	jitter_setting: JitterKind,
+	growth_strategy: TreeGrowthStrategy,
+	step_costs: StepCosts,
+	remove_detours: bool,
+	last_grow_result: Option<SnakeGrowResult>,
+	incremental: bool,
+	incremental_state: Option<IncrementalState>,
+	/// Time spent planning paths so far, for `JitterKind::JitterWithinBudget`.
+	planning_time: Duration,
+	/// How full the world must be, in percent, before the solver locks in its cycle.
+	endgame_fill: Option<usize>,
+	/// The cycle that is followed for the rest of the game once the endgame starts.
+	endgame_cycle: Option<HamiltonianCycle>,
+	phase_hook: Option<PhaseHook>,
 }
 
+/// Called with the tree and the grid after each phase of planning a path.
+type PhaseHook = Box<dyn FnMut(BuildPhase, &SpanningTree, &PathfindingGrid) + Send>;
+
+/// How often the solver re-plans by only returning the start of its path. Shorter paths let the
+/// solver take the latest food into account sooner, at the cost of planning more often.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum JitterKind {
+	/// Always return the whole path.
 	NoJitter,
+	/// Only return the given number of steps when the tree was grown over the planned path.
 	JitterWhenIndirect(usize),
+	/// Always return the given number of steps.
 	JitterAlways(usize),
+	/// Only return the given number of steps when the planned path costs more than `percent`
+	/// percent more than the cheapest route to the food, both measured in the step costs.
+	JitterWhenLonger { percent: usize, steps: usize },
+	/// Only return the given number of steps while the snake fills less than `percent` percent
+	/// of the world, as re-planning helps less once the snake is long.
+	JitterBelowFill { percent: usize, steps: usize },
+	/// Only return the given number of steps until `budget` has been spent planning paths, and
+	/// return whole paths after that.
+	JitterWithinBudget { budget: Duration, steps: usize },
+}
+
+/// How the spanning tree is grown into the space the snake's body and path don't constrain.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum TreeGrowthStrategy {
+	/// A random depth first search, which grows long winding corridors.
+	#[default]
+	RandomDfs,
+	/// Randomized Prim's algorithm, which grows short branches spreading out from each seed.
+	RandomizedPrim,
+	/// Wilson's algorithm, which picks uniformly from all of the possible spanning trees.
+	Wilson,
+	/// A deterministic breadth first search, which keeps the tree close to where it was seeded.
+	Compact,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -37,82 +87,328 @@ impl SnakeSpanningTreeSolver {
 			spanning_tree: None,
 			pathfinding_grid: None,
 			jitter_setting,
+			growth_strategy: TreeGrowthStrategy::default(),
+			step_costs: StepCosts::default(),
+			remove_detours: false,
+			last_grow_result: None,
+			incremental: false,
+			incremental_state: None,
+			planning_time: Duration::ZERO,
+			endgame_fill: None,
+			endgame_cycle: None,
+			phase_hook: None,
+		}
+	}
+
+	/// Keep the spanning tree between paths and only regrow the part of it between the
+	/// snake's head and the new food, instead of rebuilding the whole tree for every path.
+	/// Paths are cheaper to compute but tend to be longer, as the rest of the tree is fixed.
+	pub fn with_incremental(mut self, incremental: bool) -> Self {
+		self.incremental = incremental;
+		self
+	}
+
+	/// Choose how the spanning tree is grown around the snake and the path to the food.
+	pub fn with_growth_strategy(mut self, growth_strategy: TreeGrowthStrategy) -> Self {
+		self.growth_strategy = growth_strategy;
+		self
+	}
+
+	/// Weigh the steps of the path to the food, e.g. to avoid stepping out of nodes and adding
+	/// walls that constrain the rest of the tree. Jitter policies that compare the path to the
+	/// shortest distance measure both in these costs.
+	pub fn with_step_costs(mut self, step_costs: StepCosts) -> Self {
+		self.step_costs = step_costs;
+		self
+	}
+
+	/// After an incremental solver repairs its tree, rewire it so the path no longer walks around
+	/// subtrees that don't lead to the food. See `SpanningTree::remove_detours`.
+	///
+	/// Trees rebuilt from scratch are left alone. The subtrees their paths walk around are
+	/// enclosed by the path, the walls of the world and the snake, so there's nowhere past the
+	/// food to move them to. This only has an effect together with `with_incremental`.
+	pub fn with_detour_removal(mut self, remove_detours: bool) -> Self {
+		self.remove_detours = remove_detours;
+		self
+	}
+
+	/// Once the snake fills at least `percent` percent of the world, lock in the cycle around the
+	/// tree and follow it for the rest of the game instead of re-planning. Following a fixed
+	/// Hamiltonian cycle always finishes the game, and each path is only as costly as its length.
+	pub fn with_endgame(mut self, percent: usize) -> Self {
+		self.endgame_fill = Some(percent);
+		self
+	}
+
+	/// Give `on_phase` the tree and the grid after each phase of planning a path, so that
+	/// debuggers can show how the solver's settings change the tree.
+	pub fn with_phase_hook(
+		mut self,
+		on_phase: impl FnMut(BuildPhase, &SpanningTree, &PathfindingGrid) + Send + 'static,
+	) -> Self {
+		self.phase_hook = Some(Box::new(on_phase));
+		self
+	}
+
+	/// Follow the locked in cycle to the food, if the endgame has started. The cycle is dropped
+	/// if the snake's body no longer lies along it, e.g. because a new game started.
+	fn endgame_path(&mut self, world: &SnakeWorld) -> Option<Path> {
+		let cycle = self.endgame_cycle.as_ref()?;
+
+		// A snake that hasn't moved yet is at the start of a new game
+		if world.prev_direction().is_none()
+			|| cycle.len() != world.size() * world.size()
+			|| !body_follows_cycle(world, |coord| cycle.next_coord(coord))
+		{
+			self.endgame_cycle = None;
+			return None;
+		}
+
+		let head = world.snake_head_coord();
+
+		cycle.path_between(head, world.food_coord())
+	}
+}
+
+impl JitterKind {
+	/// How many steps of the planned path to return, or `None` for the whole path.
+	fn steps_to_take(
+		&self,
+		world: &SnakeWorld,
+		grow_result: SnakeGrowResult,
+		path_cost: u32,
+		shortest_distance: Option<u32>,
+		planning_time: Duration,
+	) -> Option<usize> {
+		match *self {
+			JitterKind::NoJitter => None,
+			JitterKind::JitterAlways(steps) => Some(steps),
+			JitterKind::JitterWhenIndirect(steps) => match grow_result {
+				SnakeGrowResult::SuccessWithPathOverride => Some(steps),
+				SnakeGrowResult::Success => None,
+			},
+			JitterKind::JitterWhenLonger { percent, steps } => {
+				// Re-plan to be safe if the distance isn't known
+				let longer = match shortest_distance {
+					Some(distance) => {
+						path_cost as usize * 100 > distance as usize * (100 + percent)
+					}
+					None => true,
+				};
+				longer.then_some(steps)
+			}
+			JitterKind::JitterBelowFill { percent, steps } => {
+				(!snake_fills(world, percent)).then_some(steps)
+			}
+			JitterKind::JitterWithinBudget { budget, steps } => {
+				(planning_time < budget).then_some(steps)
+			}
 		}
 	}
 }
 
+/// Whether the snake fills at least `percent` percent of the world.
+fn snake_fills(world: &SnakeWorld, percent: usize) -> bool {
+	let cells = world.size() * world.size();
+	(world.snake_length() as usize) * 100 >= cells * percent
+}
+
+/// The phases of planning a path, in the order they run. `build_spanning_tree` runs the first
+/// four, and the rest are only run by `SnakeSpanningTreeSolver` with the matching settings.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BuildPhase {
+	/// The snake's body was traced into the tree as walls and covered edges.
+	TraceSnake,
+	/// The pathfinding grid was filled outwards from the food.
+	FillPathfindingGrid,
+	/// The path from the head to the food was marked in the tree.
+	Pathfind,
+	/// The tree was grown over the rest of the world.
+	GrowTree,
+	/// The previous tree was repaired around the path to the new food, instead of being rebuilt.
+	RepairTree,
+	/// The tree was rewired to move subtrees out of the path's way.
+	RemoveDetours,
+	/// The endgame's locked in cycle was followed, without changing the tree.
+	FollowEndgameCycle,
+}
+
+/// Build the spanning tree from scratch for the current world: trace the snake's body into it,
+/// pathfind from the head to the food through it, then grow it over the rest of the world.
+/// Returns `None` if pathfinding to the food failed. Both the tree and the grid are cleared
+/// first, so they can be reused between calls.
+pub fn build_spanning_tree(
+	world: &SnakeWorld,
+	spanning_tree: &mut SpanningTree,
+	pathfinding_grid: &mut PathfindingGrid,
+) -> Option<SnakeGrowResult> {
+	build_spanning_tree_with_phases(world, spanning_tree, pathfinding_grid, |_, _, _| {})
+}
+
+/// The same as `build_spanning_tree`, but `on_phase` is given the tree and the grid after each
+/// phase, so that debuggers can show how the tree was built. If pathfinding fails, the last
+/// phase is `BuildPhase::Pathfind`.
+pub fn build_spanning_tree_with_phases(
+	world: &SnakeWorld,
+	spanning_tree: &mut SpanningTree,
+	pathfinding_grid: &mut PathfindingGrid,
+	mut on_phase: impl FnMut(BuildPhase, &SpanningTree, &PathfindingGrid),
+) -> Option<SnakeGrowResult> {
+	// Clear out anything left over from the previous path
+	spanning_tree.clear();
+	pathfinding_grid.clear();
+
+	// Step 1: Trace the snake into the spanning tree
+	spanning_tree.trace_current_snake_and_mark_edges(world);
+	on_phase(BuildPhase::TraceSnake, spanning_tree, pathfinding_grid);
+
+	// Step 2: Fill the pathfinding grid from the spanning tree
+	pathfinding_grid.fill_pathfinding_grid(world, spanning_tree);
+	on_phase(
+		BuildPhase::FillPathfindingGrid,
+		spanning_tree,
+		pathfinding_grid,
+	);
+
+	// Step 3: Pathfind through the grid, extending the tree
+	let pathfind_result = pathfinding::pathfind_on_spanning_tree(
+		world.snake_head_coord(),
+		pathfinding_grid,
+		spanning_tree,
+	);
+	on_phase(BuildPhase::Pathfind, spanning_tree, pathfinding_grid);
+
+	// Theoretically a dead end can never be reached, but we handle it just in case.
+	if pathfind_result != SnakePathfindResult::Success {
+		return None;
+	}
+
+	// Step 4: Grow the spanning tree to fill the remaining space
+	let grow_result = spanning_tree.grow_spanning_tree();
+	on_phase(BuildPhase::GrowTree, spanning_tree, pathfinding_grid);
+
+	Some(grow_result)
+}
+
 impl SnakeSolver for SnakeSpanningTreeSolver {
 	/// Grab the next path from the solver.
 	fn get_next_path(&mut self, world: &SnakeWorld) -> Path {
+		let start_time = Instant::now();
+
+		// In the endgame, follow the locked in cycle instead of re-planning
+		if let Some(path) = self.endgame_path(world) {
+			if let (Some(on_phase), Some(tree), Some(grid)) = (
+				&mut self.phase_hook,
+				&self.spanning_tree,
+				&self.pathfinding_grid,
+			) {
+				on_phase(BuildPhase::FollowEndgameCycle, tree, grid);
+			}
+
+			self.last_grow_result = None;
+			self.planning_time += start_time.elapsed();
+			return path;
+		}
+
 		// Fetch the cached data structures to avoid re-allocations
 		let spanning_tree = self
 			.spanning_tree
-			.get_or_insert_with(|| SpanningTree::new(world.size()));
-		let pathfinding_grid = self
-			.pathfinding_grid
-			.get_or_insert_with(|| PathfindingGrid::new(world.size()));
-
-		// Clear them just in case after fetching
-		spanning_tree.clear();
-		pathfinding_grid.clear();
-
-		// Step 1: Trace the snake into the spanning tree
-		spanning_tree.trace_current_snake_and_mark_edges(&world);
-
-		// Step 2: Fill the pathfinding grid from the spanning tree
-		pathfinding_grid.fill_pathfinding_grid(world, spanning_tree);
-
-		// Step 3: Pathfind through the grid, extending the tree
-		let pathfind_result = pathfinding::pathfind_on_spanning_tree(
-			world.snake_head_coord(),
-			pathfinding_grid,
-			spanning_tree,
-		);
+			.get_or_insert_with(|| SpanningTree::new(world.size(), self.growth_strategy));
+		let pathfinding_grid = self.pathfinding_grid.get_or_insert_with(|| {
+			PathfindingGrid::new(world.size()).with_step_costs(self.step_costs)
+		});
 
-		// Process the pathfind result. Theoretically a dead end can never be reached,
-		// but we handle it just in case.
-		match pathfind_result {
-			SnakePathfindResult::Success => {}
-			SnakePathfindResult::ReachedDeadEnd => {
-				// Theoretically this can't be reached, but we handle it just in case.
-
-				println!("Reached dead end while pathfinding");
-				println!("Pathfinding failed, returning a killing path");
-
-				// Return a path that goes backwards into the snake to kill it
-				let mut path = Path::new();
-				path.push(
-					world
-						.calculate_snake_path_from_head()
-						.iter_directions()
-						.nth(0)
-						.unwrap(),
-				);
-				return path;
+		self.last_grow_result = None;
+
+		let mut report_phase = |phase, tree: &SpanningTree, grid: &PathfindingGrid| {
+			if let Some(on_phase) = &mut self.phase_hook {
+				on_phase(phase, tree, grid);
 			}
-		}
+		};
 
-		// Step 4: Grow the spanning tree to fill the remaining space
-		let grow_result = spanning_tree.grow_spanning_tree();
+		// In incremental mode, try repairing the previous tree before rebuilding it
+		let repaired = match &mut self.incremental_state {
+			Some(state) if self.incremental => state.repair(world, spanning_tree, pathfinding_grid),
+			_ => None,
+		};
 
-		// Step 5: Trace the spanning tree to create the snake path
-		let path = spanning_tree.build_snake_path(&world);
+		let was_repaired = repaired.is_some();
+		let (grow_result, path) = match repaired {
+			Some(repaired) => {
+				report_phase(BuildPhase::RepairTree, spanning_tree, pathfinding_grid);
+				repaired
+			}
+			None => {
+				self.incremental_state = None;
 
-		// Handle the growth result. We choose different step counts depending on the result and the jitter setting.
-		let take = match grow_result {
-			SnakeGrowResult::Success => {
-				if let JitterKind::JitterAlways(num) = self.jitter_setting {
-					Some(num)
-				} else {
-					None
+				match build_spanning_tree_with_phases(
+					world,
+					spanning_tree,
+					pathfinding_grid,
+					&mut report_phase,
+				) {
+					// Step 5: Trace the spanning tree to create the snake path
+					Some(grow_result) => (grow_result, spanning_tree.build_snake_path(world)),
+					None => {
+						eprintln!("Reached dead end while pathfinding");
+						eprintln!("Pathfinding failed, returning a killing path");
+
+						// Return a path that goes backwards into the snake to kill it
+						let mut path = Path::new();
+						path.push(
+							world
+								.calculate_snake_path_from_head()
+								.iter_directions()
+								.nth(0)
+								.unwrap(),
+						);
+						return path;
+					}
 				}
 			}
-			SnakeGrowResult::SuccessWithPathOverride => match self.jitter_setting {
-				JitterKind::JitterWhenIndirect(num) => Some(num),
-				JitterKind::JitterAlways(num) => Some(num),
-				JitterKind::NoJitter => None,
-			},
 		};
+		self.last_grow_result = Some(grow_result);
+
+		// Move the subtrees the path detours around out of its way. A freshly built tree's detours
+		// are boxed in by the path itself, so only repaired trees have any that can be moved.
+		let path = if self.remove_detours && was_repaired {
+			let removed = spanning_tree.remove_detours(world);
+			report_phase(BuildPhase::RemoveDetours, spanning_tree, pathfinding_grid);
+			if removed > 0 {
+				spanning_tree.build_snake_path(world)
+			} else {
+				path
+			}
+		} else {
+			path
+		};
+
+		// Lock in the cycle once the snake fills enough of the world, and stop re-planning
+		if self
+			.endgame_fill
+			.is_some_and(|percent| snake_fills(world, percent))
+		{
+			let cycle = spanning_tree.hamiltonian_cycle(world.snake_head_coord());
+			if cycle.verify().is_ok() {
+				self.endgame_cycle = Some(cycle);
+				// The cycle is followed instead of the pending path, so if the endgame is ever
+				// dropped the tree has to be rebuilt rather than repaired
+				self.incremental_state = None;
+				self.planning_time += start_time.elapsed();
+				return path;
+			}
+		}
+
+		// Handle the growth result. We choose different step counts depending on the result and the jitter setting.
+		let shortest_distance = pathfinding_grid.shortest_distance_from(world.snake_head_coord());
+		let take = self.jitter_setting.steps_to_take(
+			world,
+			grow_result,
+			pathfinding_grid.path_cost(world.snake_head_coord(), &path),
+			shortest_distance,
+			self.planning_time,
+		);
 
 		// Shorten the path if necessary
 		let path = if let Some(take) = take {
@@ -127,9 +423,26 @@ impl SnakeSolver for SnakeSpanningTreeSolver {
 			path
 		};
 
+		if self.incremental {
+			self.incremental_state
+				.get_or_insert_with(|| IncrementalState::new(world, spanning_tree))
+				.set_pending_path(&path, world.food_coord());
+		}
+
+		self.planning_time += start_time.elapsed();
+
 		path
 	}
 
+	fn last_path_was_overridden(&self) -> bool {
+		self.last_grow_result == Some(SnakeGrowResult::SuccessWithPathOverride)
+	}
+
+	fn hamiltonian_cycle(&self, world: &SnakeWorld) -> Option<HamiltonianCycle> {
+		let spanning_tree = self.spanning_tree.as_ref()?;
+		Some(spanning_tree.hamiltonian_cycle(world.snake_head_coord()))
+	}
+
 	// UI function to decorate the widget with pathfinding metadata
 	fn decorate_widget<'a>(&'a self, mut widget: SnakeWorldViewer<'a>) -> SnakeWorldViewer<'a> {
 		if let Some(tree) = &self.spanning_tree {
@@ -26,3 +26,16 @@ pub fn calculate_following_out_edge(coord: Coord) -> (Coord, Direction) {
 	let [_, out] = get_valid_dirs_from_coord(coord);
 	(meta_coord, out)
 }
+
+/// The inverse of `calculate_following_out_edge`: given a spanning tree node and a direction,
+/// return the cell of the node that moves outwards along the edge in that direction.
+pub fn calculate_out_cell(node: Coord, dir: Direction) -> Coord {
+	let offset = match dir {
+		Direction::Up => Offset::new(0, 0),
+		Direction::Right => Offset::new(1, 0),
+		Direction::Down => Offset::new(1, 1),
+		Direction::Left => Offset::new(0, 1),
+	};
+
+	node.map_values(|v| v * 2) + offset
+}
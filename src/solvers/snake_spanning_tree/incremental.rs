@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+
+use crate::{
+	array2d::Array2D,
+	direction::Direction,
+	grid_graph::GridGraph,
+	path::Path,
+	snake::{Cell, SnakeWorld},
+	solvers::utils::get_valid_dirs_from_coord,
+	Coord,
+};
+
+use super::{
+	coordinates::calculate_inner_tree_coord,
+	pathfinding::{self, PathfindingGrid, SnakePathfindResult},
+	spanning_tree::{SnakeGrowResult, SpanTreeEdgeType, SpanningTree},
+};
+
+/// A cell of the snake's body, and when the tail will leave it.
+struct BodyCell {
+	coord: Coord,
+	expires_at: u64,
+	/// The tree edge the snake crossed when it moved clockwise out of this cell, if it did.
+	covered_edge: Option<(Coord, Direction)>,
+}
+
+/// Tracks the snake between paths so that the previous spanning tree can be repaired,
+/// rather than being rebuilt from scratch every time a new food appears.
+///
+/// The snake always follows the cycle around the previous tree, so that tree is still valid
+/// for the snake's current body. The cycle from the head to the new food only passes over
+/// free cells, and any node whose cells all lie on that stretch of the cycle can't hold
+/// any of the body's walls. Those nodes form whole subtrees, so they can be removed from the
+/// tree and regrown around a new path to the food while the rest of the tree is kept.
+pub struct IncrementalState {
+	/// The snake's body from the tail to the head.
+	body: VecDeque<BodyCell>,
+	/// How many body cells cross each spanning tree edge.
+	covered_support: GridGraph<u16>,
+	/// Scratch space for counting how many cells of each node lie between the head and the food.
+	node_cells: Array2D<u8>,
+	step: u64,
+	length: u32,
+	food: Coord,
+	/// The last path returned, which the snake is expected to have followed.
+	pending_path: Path,
+}
+
+impl IncrementalState {
+	/// Start tracking the snake from a spanning tree that was just built for the world.
+	pub fn new(world: &SnakeWorld, tree: &mut SpanningTree) -> Self {
+		let mut state = Self {
+			body: VecDeque::new(),
+			covered_support: GridGraph::new(world.size() / 2, 0),
+			node_cells: Array2D::new(world.size() / 2, 0),
+			step: 0,
+			length: world.snake_length(),
+			food: world.food_coord(),
+			pending_path: Path::new(),
+		};
+
+		// Collect the body from the tail to the head
+		let mut cells = vec![world.snake_head_coord()];
+		for dir in world.calculate_snake_path_from_head().iter_directions() {
+			cells.push(cells.last().unwrap().go_towards(dir));
+		}
+		cells.reverse();
+
+		for (i, &coord) in cells.iter().enumerate() {
+			let Some(&Cell::Snake(value)) = world.get_cell(coord) else {
+				unreachable!("Snake path left the snake's body")
+			};
+
+			// A cell with value `n` is culled after `n + 1` more steps
+			state.body.push_back(BodyCell {
+				coord,
+				expires_at: value as u64 + 1,
+				covered_edge: None,
+			});
+
+			if let Some(&next) = cells.get(i + 1) {
+				state.record_step(coord, next, tree);
+			}
+		}
+
+		state
+	}
+
+	/// Remember the path that was returned, to replay it on the next call.
+	pub fn set_pending_path(&mut self, path: &Path, food: Coord) {
+		self.pending_path = path.clone();
+		self.food = food;
+	}
+
+	/// Repair the spanning tree for the world's new food and return the path to it, or `None` if
+	/// the world doesn't match the tracked snake or the tree couldn't be repaired, in which case
+	/// it should be rebuilt.
+	pub fn repair(
+		&mut self,
+		world: &SnakeWorld,
+		tree: &mut SpanningTree,
+		pathfinding_grid: &mut PathfindingGrid,
+	) -> Option<(SnakeGrowResult, Path)> {
+		// Step 1: Catch up with the snake's movement since the last path
+		if !self.replay_pending_path(world, tree) {
+			return None;
+		}
+
+		tree.clear_future_edges();
+		pathfinding_grid.clear();
+
+		// Step 2: Find the nodes that the current cycle only visits between the head and the food
+		let mut touched_nodes = Vec::new();
+		let mut current = world.snake_head_coord();
+		for dir in tree.try_build_snake_path(world)?.iter_directions() {
+			current = current.go_towards(dir);
+
+			let count = &mut self.node_cells[current.map_values(|v| v / 2)];
+			if *count == 0 {
+				touched_nodes.push(current.map_values(|v| v / 2));
+			}
+			*count += 1;
+		}
+
+		let mut regrown_nodes = Vec::new();
+		for node in touched_nodes {
+			if self.node_cells[node] == 4 {
+				regrown_nodes.push(node);
+			}
+			self.node_cells[node] = 0;
+		}
+
+		// Step 3: Remove those nodes from the tree, leaving the rest of it connected
+		for &node in regrown_nodes.iter() {
+			tree.untake_node(node);
+		}
+
+		// Step 4: Pathfind to the food through the freed space, stopping the fill early
+		pathfinding_grid.fill_pathfinding_grid_until(world, tree, world.snake_head_coord());
+		let pathfind_result = pathfinding::pathfind_on_spanning_tree(
+			world.snake_head_coord(),
+			pathfinding_grid,
+			tree,
+		);
+		if pathfind_result != SnakePathfindResult::Success {
+			return None;
+		}
+
+		// Step 5: Regrow the removed nodes, seeding only from them and their neighbours
+		let mut candidates = regrown_nodes.clone();
+		for &node in regrown_nodes.iter() {
+			candidates.extend(
+				Direction::each()
+					.map(|dir| node.go_towards(dir))
+					.filter(|&coord| tree.is_in_bounds(coord)),
+			);
+		}
+
+		let grow_result = tree.grow_spanning_tree_from(&candidates);
+
+		if regrown_nodes
+			.iter()
+			.any(|&node| !tree.is_tree_node_taken(node))
+		{
+			return None;
+		}
+
+		// Step 6: Make sure the repaired tree still leads to the food
+		let path = tree.try_build_snake_path(world)?;
+
+		Some((grow_result, path))
+	}
+
+	/// Step the tracked snake along the pending path and check that it matches the world.
+	fn replay_pending_path(&mut self, world: &SnakeWorld, tree: &mut SpanningTree) -> bool {
+		let pending_path = std::mem::take(&mut self.pending_path);
+
+		for dir in pending_path.iter_directions() {
+			let head = self.body.back().unwrap().coord;
+			let next = head.go_towards(dir);
+
+			if !self.record_step(head, next, tree) {
+				return false;
+			}
+
+			self.step += 1;
+			if next == self.food {
+				self.length += 3;
+			}
+
+			self.body.push_back(BodyCell {
+				coord: next,
+				expires_at: self.step + self.length as u64,
+				covered_edge: None,
+			});
+
+			// Cull the tail the same way the world does
+			while self.body.front().unwrap().expires_at <= self.step {
+				let cell = self.body.pop_front().unwrap();
+				if let Some((coord, dir)) = cell.covered_edge {
+					self.release_covered_edge(coord, dir, tree);
+				}
+			}
+		}
+
+		let expected_value =
+			|cell: &BodyCell| Cell::Snake((cell.expires_at - self.step - 1) as u32);
+		let head = self.body.back().unwrap();
+		let tail = self.body.front().unwrap();
+
+		head.coord == world.snake_head_coord()
+			&& self.length == world.snake_length()
+			&& world.get_cell(head.coord) == Some(&expected_value(head))
+			&& world.get_cell(tail.coord) == Some(&expected_value(tail))
+	}
+
+	/// Record the snake moving from the current head to the next cell, marking the edge it
+	/// crosses if it moved clockwise. Returns false if the move doesn't follow the tree.
+	fn record_step(&mut self, head: Coord, next: Coord, tree: &mut SpanningTree) -> bool {
+		let [clockwise, _] = get_valid_dirs_from_coord(head);
+		if head.go_towards(clockwise) != next {
+			return true;
+		}
+
+		let (coord, dir) = calculate_inner_tree_coord(head, clockwise);
+		if tree.get_edge(coord, dir) == Some(&SpanTreeEdgeType::Wall) {
+			return false;
+		}
+
+		if let Some(&support) = self.covered_support.get_edge(coord, dir) {
+			self.covered_support.set_edge(coord, dir, support + 1);
+			tree.set_edge(coord, dir, SpanTreeEdgeType::CoveredByCurrentSnake);
+			self.body.back_mut().unwrap().covered_edge = Some((coord, dir));
+		}
+
+		true
+	}
+
+	fn release_covered_edge(&mut self, coord: Coord, dir: Direction, tree: &mut SpanningTree) {
+		let support = self.covered_support.get_edge(coord, dir).unwrap() - 1;
+		self.covered_support.set_edge(coord, dir, support);
+
+		if support == 0
+			&& tree.get_edge(coord, dir) == Some(&SpanTreeEdgeType::CoveredByCurrentSnake)
+		{
+			tree.set_edge(coord, dir, SpanTreeEdgeType::Free);
+		}
+	}
+}
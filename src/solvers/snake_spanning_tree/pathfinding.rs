@@ -3,6 +3,7 @@ use std::collections::VecDeque;
 use crate::{
 	array2d::Array2D,
 	direction::Direction,
+	path::Path,
 	snake::{Cell, SnakeWorld},
 	solvers::utils::get_valid_dirs_from_coord,
 	Coord,
@@ -17,7 +18,13 @@ use super::{
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum SnakePathfindResult {
 	Success,
+	/// Neither of the cells the snake could step onto were reached by the fill.
 	ReachedDeadEnd,
+	/// The grid was only partially filled, and the path needed a cell that wasn't reached.
+	ReachedUnfilledCell,
+	/// The path went around in a loop without reaching the food. This can only happen when the
+	/// snake's body doesn't follow the tree's stepping rules, e.g. in a world played by a human.
+	ReachedLoop,
 }
 
 trait PathfindValue: Eq + Sized {
@@ -36,8 +43,73 @@ impl PathfindValue for u32 {
 	const UNINITIALIZED: Self = 0;
 }
 
+/// The cost of each step when filling the pathfinding grid. By default every step costs 1,
+/// which finds the route to the food with the fewest steps.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StepCosts {
+	/// The cost of a step clockwise around a node's cells.
+	pub clockwise: u32,
+	/// The cost of a step out of a node, which adds a wall to the tree.
+	pub out: u32,
+	/// The extra cost of a step in a different direction to the step after it.
+	///
+	/// This is a heuristic: each cell only remembers the first step of its own cheapest route,
+	/// so a route that costs more to a cell but turns less after it is never considered. With a
+	/// turn cost the path isn't always the cheapest one, but every path is still valid.
+	pub turn: u32,
+}
+
+impl Default for StepCosts {
+	fn default() -> Self {
+		Self {
+			clockwise: 1,
+			out: 1,
+			turn: 0,
+		}
+	}
+}
+
+impl StepCosts {
+	/// The cost of taking a step of the given kind in direction `dir`, followed by a step in
+	/// direction `next_dir` if the path continues.
+	fn step(&self, kind: GridStepKind, dir: Direction, next_dir: Option<Direction>) -> u32 {
+		let base = match kind {
+			GridStepKind::Clockwise => self.clockwise,
+			GridStepKind::Out => self.out,
+		};
+
+		if next_dir.is_some_and(|next_dir| next_dir != dir) {
+			base + self.turn
+		} else {
+			base
+		}
+	}
+
+	fn is_uniform(&self) -> bool {
+		self.clockwise == self.out && self.turn == 0
+	}
+
+	fn max_step(&self) -> u32 {
+		self.clockwise.max(self.out) + self.turn
+	}
+}
+
+/// The distance of each cell of the snake world from the food, following the spanning tree's
+/// stepping rules. A cell's value is one more than the cost of the cheapest route from it to
+/// the food, or `0` if it wasn't reached. With the default step costs, the cost is the number
+/// of steps. Dereferences to the grid of values.
 pub struct PathfindingGrid {
 	grid: Array2D<u32>,
+	/// The lowest distance each cell has been queued with during the fill.
+	queued_dist: Array2D<u32>,
+	/// The direction of the first step of each cell's cheapest route to the food, which is only
+	/// tracked when turns have a cost.
+	leaving_dir: Array2D<Option<Direction>>,
+	/// The fill's queue, bucketed by distance.
+	buckets: Vec<VecDeque<Coord>>,
+	touched: Vec<Coord>,
+	complete: bool,
+	step_costs: StepCosts,
 }
 
 impl std::ops::Deref for PathfindingGrid {
@@ -48,29 +120,121 @@ impl std::ops::Deref for PathfindingGrid {
 	}
 }
 
-impl std::ops::DerefMut for PathfindingGrid {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.grid
-	}
-}
-
 impl PathfindingGrid {
+	/// Create an empty grid for a snake world of the given size.
 	pub fn new(world_size: usize) -> Self {
 		let grid = Array2D::new(world_size, PathfindValue::UNINITIALIZED);
-		Self { grid }
+		Self {
+			grid,
+			queued_dist: Array2D::new(world_size, u32::MAX),
+			leaving_dir: Array2D::new(world_size, None),
+			buckets: Vec::new(),
+			touched: Vec::new(),
+			complete: true,
+			step_costs: StepCosts::default(),
+		}
+	}
+
+	/// Weigh the steps of the fill, so that routes which are cheaper by these costs are preferred
+	/// over routes with fewer steps. Clockwise and out steps cost at least 1.
+	pub fn with_step_costs(mut self, step_costs: StepCosts) -> Self {
+		self.step_costs = StepCosts {
+			clockwise: step_costs.clockwise.max(1),
+			out: step_costs.out.max(1),
+			turn: step_costs.turn,
+		};
+		self
+	}
+
+	/// The cost of a step of the given kind from `from`, given the route the fill found from the
+	/// cell it steps onto.
+	fn step_cost(&self, from: Coord, kind: GridStepKind) -> u32 {
+		let [clockwise, out] = get_valid_dirs_from_coord(from);
+		let dir = match kind {
+			GridStepKind::Clockwise => clockwise,
+			GridStepKind::Out => out,
+		};
+		let next_dir = self
+			.leaving_dir
+			.get(from.go_towards(dir))
+			.copied()
+			.flatten();
+
+		self.step_costs.step(kind, dir, next_dir)
 	}
 
 	/// Given a snake world and a spanning tree, fill the grid starting from the food coordinate outwards.
 	pub fn fill_pathfinding_grid(&mut self, world: &SnakeWorld, graph: &SpanningTree) {
+		self.fill(world, graph, None);
+	}
+
+	/// Like `fill_pathfinding_grid`, but stop shortly after reaching the cells that `head` can
+	/// step onto, leaving the rest of the grid unfilled.
+	pub fn fill_pathfinding_grid_until(
+		&mut self,
+		world: &SnakeWorld,
+		graph: &SpanningTree,
+		head: Coord,
+	) {
+		self.fill(world, graph, Some(head));
+	}
+
+	fn fill(&mut self, world: &SnakeWorld, graph: &SpanningTree, head: Option<Coord>) {
 		let starting_coord = world.food_coord();
+		let step_costs = self.step_costs;
+
+		// The cells the head can step onto, and how far to fill once the first of them is reached
+		let head_neighbours =
+			head.map(|head| get_valid_dirs_from_coord(head).map(|dir| head.go_towards(dir)));
+		let mut max_dist = None;
+		self.complete = true;
+
+		// Cells are queued in buckets by their distance, which is Dijkstra's algorithm for small
+		// integer costs. No step costs more than `max_step`, so the buckets can be reused in a ring.
+		// With uniform costs this visits cells in the same order as a plain BFS queue, and the
+		// first distance a cell is queued with is final, so cells never need to be queued again.
+		let uniform = step_costs.is_uniform();
+		let bucket_count = step_costs.max_step() as usize + 1;
+		let mut buckets = std::mem::take(&mut self.buckets);
+		buckets.resize_with(bucket_count, VecDeque::new);
+		let mut queued = 1;
+
+		buckets[<u32 as PathfindValue>::START as usize % bucket_count].push_back(starting_coord);
+		self.queued_dist.set(starting_coord, PathfindValue::START);
+		self.leaving_dir.set(starting_coord, None);
+		self.touched.push(starting_coord);
+
+		let mut dist: u32 = PathfindValue::START;
+		let mut bucket = dist as usize % bucket_count;
+		while queued > 0 {
+			let Some(coord) = buckets[bucket].pop_front() else {
+				dist += 1;
+				bucket = if bucket + 1 == bucket_count {
+					0
+				} else {
+					bucket + 1
+				};
+				continue;
+			};
+			queued -= 1;
 
-		// We use a basic queue system for A*
-		let mut queue = VecDeque::new();
-		queue.push_back((starting_coord, PathfindValue::START));
+			// Skip cells that were queued again with a shorter distance
+			if !uniform && self.queued_dist[coord] != dist {
+				continue;
+			}
+
+			// Once the cells around the head's closest neighbour are set, we can stop
+			if max_dist.is_some_and(|max_dist| dist > max_dist) {
+				self.complete = false;
+				break;
+			}
 
-		while let Some((coord, dist)) = queue.pop_front() {
 			// Visit the cell and set the value
-			self.set(coord, dist);
+			self.grid.set(coord, dist);
+
+			if max_dist.is_none() && head_neighbours.is_some_and(|cells| cells.contains(&coord)) {
+				max_dist = Some(dist + step_costs.max_step());
+			}
 
 			// Get the valid locations that a snake could come from into this cell.
 			// We use .opposite and reverse the out/clockwise because we're checking where
@@ -100,8 +264,10 @@ impl PathfindingGrid {
 					continue;
 				};
 
-				if current_val.has_been_visited() {
-					// If has already been visited, continue
+				if current_val.has_been_visited()
+					&& (uniform || current_val != PathfindValue::MARKED)
+				{
+					// If the cell's distance is already final, continue
 					continue;
 				}
 
@@ -119,15 +285,94 @@ impl PathfindingGrid {
 					}
 				}
 
+				// Only queue the cell if this is the cheapest way to it so far
+				let step_dir = dir.opposite();
+				let next_dir = if step_costs.turn > 0 {
+					self.leaving_dir[coord]
+				} else {
+					None
+				};
+				let step_cost = step_costs.step(kind, step_dir, next_dir);
+				let next_dist = dist + step_cost;
+				if !uniform && next_dist >= self.queued_dist[next] {
+					continue;
+				}
+
 				// If everything is valid, we mark the cell and add it to the queue.
-				self.set(next, PathfindValue::MARKED);
-				queue.push_back((next, dist + 1));
+				if !current_val.has_been_visited() {
+					self.grid.set(next, PathfindValue::MARKED);
+					self.touched.push(next);
+				}
+				if !uniform {
+					self.queued_dist.set(next, next_dist);
+				}
+				if step_costs.turn > 0 {
+					self.leaving_dir.set(next, Some(step_dir));
+				}
+				let mut next_bucket = bucket + step_cost as usize;
+				if next_bucket >= bucket_count {
+					next_bucket -= bucket_count;
+				}
+				buckets[next_bucket].push_back(next);
+				queued += 1;
 			}
 		}
+
+		// Keep the emptied buckets for the next fill
+		buckets.iter_mut().for_each(VecDeque::clear);
+		self.buckets = buckets;
 	}
 
+	/// The cost of the cheapest route from `head` to the food through the filled grid, if the
+	/// fill reached any of the cells the head can step onto. With the default step costs, this
+	/// is the fewest steps it takes.
+	pub fn shortest_distance_from(&self, head: Coord) -> Option<u32> {
+		// A cell's value is one more than its distance from the food
+		[GridStepKind::Clockwise, GridStepKind::Out]
+			.into_iter()
+			.zip(get_valid_dirs_from_coord(head))
+			.filter_map(|(kind, dir)| {
+				let value = *self.get(head.go_towards(dir))?;
+				(value.has_been_visited() && value != PathfindValue::MARKED)
+					.then(|| value - <u32 as PathfindValue>::START + self.step_cost(head, kind))
+			})
+			.min()
+	}
+
+	/// The cost of following `path` from `head` with this grid's step costs, in the same units as
+	/// `shortest_distance_from`. Steps that aren't clockwise around a node are costed as steps out.
+	pub fn path_cost(&self, head: Coord, path: &Path) -> u32 {
+		let mut coord = head;
+		let mut dirs = path.iter_directions().peekable();
+		let mut cost = 0;
+		while let Some(dir) = dirs.next() {
+			let [clockwise, _] = get_valid_dirs_from_coord(coord);
+			let kind = if dir == clockwise {
+				GridStepKind::Clockwise
+			} else {
+				GridStepKind::Out
+			};
+
+			cost += self.step_costs.step(kind, dir, dirs.peek().copied());
+			coord = coord.go_towards(dir);
+		}
+		cost
+	}
+
+	/// The grid of values, where each cell's value is one more than its distance from the food.
+	pub fn distances(&self) -> &Array2D<u32> {
+		&self.grid
+	}
+
+	/// Reset every cell that was visited since the last clear.
 	pub fn clear(&mut self) {
-		self.grid.fill(PathfindValue::UNINITIALIZED);
+		let uniform = self.step_costs.is_uniform();
+		for coord in self.touched.drain(..) {
+			self.grid.set(coord, PathfindValue::UNINITIALIZED);
+			if !uniform {
+				self.queued_dist.set(coord, u32::MAX);
+			}
+		}
 	}
 }
 
@@ -139,11 +384,12 @@ pub fn pathfind_on_spanning_tree(
 ) -> SnakePathfindResult {
 	let mut current = from;
 
-	loop {
+	// A path that doesn't loop visits each cell at most once
+	for _ in 0..=grid.count() {
 		let current_value = *grid.get(current).unwrap();
 		if current_value == PathfindValue::START {
 			// If we reached the minimum value, we're done.
-			break;
+			return SnakePathfindResult::Success;
 		}
 
 		// Get the valid directions from the coord
@@ -152,8 +398,11 @@ pub fn pathfind_on_spanning_tree(
 		let get_value_at = |dir: Direction| {
 			let coord = current.go_towards(dir);
 			grid.get(coord).and_then(|&v| {
-				if v == PathfindValue::UNINITIALIZED {
+				if v == PathfindValue::UNINITIALIZED && grid.complete {
 					None
+				} else if v == PathfindValue::UNINITIALIZED {
+					// The cell may just not have been reached yet, so it's further than any filled cell
+					Some(PathfindValue::MARKED)
 				} else {
 					Some(v)
 				}
@@ -161,21 +410,29 @@ pub fn pathfind_on_spanning_tree(
 		};
 
 		// Get the vales in the valid directions. If a cell is out of bounds or uninitialized, then it's value is None.
+		// Cells that a partial fill didn't reach have the value `MARKED`.
 		let clockwise_value = get_value_at(clockwise);
 		let out_value = get_value_at(out);
 
+		// Add the cost of stepping onto each cell, keeping unreached cells further than any other
+		let with_step_cost = |value: Option<u32>, kind: GridStepKind| {
+			value.map(|value| value.saturating_add(grid.step_cost(current, kind)))
+		};
+		let clockwise_cost = with_step_cost(clockwise_value, GridStepKind::Clockwise);
+		let out_cost = with_step_cost(out_value, GridStepKind::Out);
+
 		// If both are None, then we've reached a dead end. This shouldn't be possible, but we handle it anyway.
-		if clockwise_value == None && out_value == None {
+		if clockwise_value.is_none() && out_value.is_none() {
 			return SnakePathfindResult::ReachedDeadEnd;
 		}
 
-		let next_dir = if clockwise_value == None {
+		let next_dir = if clockwise_value.is_none() {
 			// If we can't go clockwise, then go out
 			GridStepKind::Out
-		} else if out_value == None {
+		} else if out_value.is_none() {
 			// If we can't go out, then go clockwise
 			GridStepKind::Clockwise
-		} else if clockwise_value < out_value {
+		} else if clockwise_cost < out_cost {
 			// If clockwise is smaller then out, go clockwise
 			GridStepKind::Clockwise
 		} else if tree.can_walk_out_from(current) {
@@ -185,6 +442,15 @@ pub fn pathfind_on_spanning_tree(
 			GridStepKind::Clockwise
 		};
 
+		// If the cell we chose wasn't reached by a partial fill, we can't know if it's the right way
+		let next_value = match next_dir {
+			GridStepKind::Clockwise => clockwise_value,
+			GridStepKind::Out => out_value,
+		};
+		if next_value == Some(PathfindValue::MARKED) {
+			return SnakePathfindResult::ReachedUnfilledCell;
+		}
+
 		// Set the edge accordingly
 		let (coord, dir) = calculate_following_out_edge(current);
 		match next_dir {
@@ -192,7 +458,7 @@ pub fn pathfind_on_spanning_tree(
 				tree.try_set_edge(coord, dir, SpanTreeEdgeType::Wall);
 			}
 			GridStepKind::Clockwise => {
-				tree.try_set_edge(coord, dir, SpanTreeEdgeType::CoveredByFutureSnake);
+				tree.mark_covered_by_future_snake(coord, dir);
 			}
 		}
 
@@ -204,5 +470,5 @@ pub fn pathfind_on_spanning_tree(
 		current = current.go_towards(next_dir);
 	}
 
-	SnakePathfindResult::Success
+	SnakePathfindResult::ReachedLoop
 }
@@ -3,27 +3,39 @@ use std::collections::VecDeque;
 use rand::Rng;
 
 use crate::{
+	array2d::Array2D,
 	direction::Direction,
 	grid_graph::GridGraph,
 	path::Path,
 	snake::{Cell, SnakeWorld},
-	solvers::utils::get_valid_dirs_from_coord,
+	solvers::{hamiltonian_cycle::HamiltonianCycle, utils::get_valid_dirs_from_coord},
 	Coord, Offset,
 };
 
-use super::coordinates::{calculate_following_out_edge, calculate_inner_tree_coord};
+use super::{
+	coordinates::{calculate_following_out_edge, calculate_inner_tree_coord, calculate_out_cell},
+	TreeGrowthStrategy,
+};
 
+/// The state of an edge between two nodes of the spanning tree. Each node covers a 2x2 block
+/// of cells in the snake world, and the cycle around the tree walks around its walls.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum SpanTreeEdgeType {
+	/// Not part of the tree.
 	Free,
+	/// Part of the tree, which the cycle can't cross.
 	Wall,
+	/// Not part of the tree, as the snake's body crosses it.
 	CoveredByCurrentSnake,
+	/// Not part of the tree, as the planned path to the food crosses it.
 	CoveredByFutureSnake,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum SnakeGrowResult {
+	/// The tree was grown without crossing the planned path.
 	Success,
+	/// The tree had to be grown over the planned path, so the snake's path differs from it.
 	SuccessWithPathOverride,
 }
 
@@ -38,14 +50,35 @@ impl SpanTreeEdgeType {
 	}
 }
 
+/// A spanning tree over the 2x2 blocks of a snake world, where node `n` covers the cells from
+/// `2n` to `2n + 1`. Dereferences to the graph of its edges.
 pub struct SpanningTree {
 	graph: GridGraph<SpanTreeEdgeType>,
+	future_edges: Vec<(Coord, Direction)>,
+	growth_strategy: TreeGrowthStrategy,
+	/// Scratch space marking the region being grown into, used by Wilson's algorithm.
+	region: Array2D<bool>,
+	/// Scratch space for the direction each node was last left in during a random walk.
+	walk_dirs: Array2D<Direction>,
+	/// Scratch space for the cells of the path to the food and their positions along it,
+	/// used when removing detours.
+	path_cells: Vec<Coord>,
+	path_index: Array2D<u32>,
 }
 
 impl SpanningTree {
-	pub fn new(world_size: usize) -> Self {
-		let graph = GridGraph::new(world_size as usize / 2, SpanTreeEdgeType::Free);
-		Self { graph }
+	/// Create an empty tree for a snake world of the given (even) size.
+	pub fn new(world_size: usize, growth_strategy: TreeGrowthStrategy) -> Self {
+		let graph = GridGraph::new(world_size / 2, SpanTreeEdgeType::Free);
+		Self {
+			graph,
+			future_edges: Vec::new(),
+			growth_strategy,
+			region: Array2D::new(world_size / 2, false),
+			walk_dirs: Array2D::new(world_size / 2, Direction::Up),
+			path_cells: Vec::new(),
+			path_index: Array2D::new(world_size, u32::MAX),
+		}
 	}
 
 	/// Follow a snake's path from the head, cell by cell, and mark which edges
@@ -86,47 +119,79 @@ impl SpanningTree {
 	/// grow over `CoveredByFutureSnake` edges. `SnakeGrowResult` reflects whether
 	/// the spanning tree was grown over any `CoveredByFutureSnake` edges.
 	pub fn grow_spanning_tree(&mut self) -> SnakeGrowResult {
-		let mut allow_covered = false;
+		self.seed_untaken_tree();
+
+		// Only taken nodes next to an untaken node can seed a new region
+		let frontier = self
+			.iter_all_coords()
+			.filter(|&coord| self.is_tree_node_taken(coord) && self.borders_untaken_node(coord))
+			.collect::<Vec<_>>();
+
+		self.grow_spanning_tree_from(&frontier)
+	}
+
+	/// Like `grow_spanning_tree`, but only seeds new regions from the given candidate nodes
+	/// and the nodes that seeding leaves behind, so every untaken region must border at least
+	/// one candidate for the tree to be complete.
+	///
+	/// Candidates are kept in a frontier ordered the same way as `iter_all_coords`, so regions
+	/// are seeded in the same order as scanning the whole grid after every seed would.
+	pub(crate) fn grow_spanning_tree_from(&mut self, candidates: &[Coord]) -> SnakeGrowResult {
+		let size = self.size();
+		let scan_index = |coord: Coord| coord.x as usize * size + coord.y as usize;
+		let scan_coord = |index: usize| Coord::new((index / size) as i32, (index % size) as i32);
+
+		let mut frontier = ScanFrontier::new(size * size);
+		for &coord in candidates {
+			frontier.push(scan_index(coord));
+		}
+		let mut next_pass = Vec::new();
+		let mut left_behind = Vec::new();
 
 		let mut seeded_covered_edge = false;
 
-		'iter: loop {
-			// We loop over each coodinate and direction to find a taken coordinate with a neighbouring untaken coordinate
-			for coord in self.iter_all_coords() {
+		// We iterate twice, once without allowing covered edges, and once with.
+		for allow_covered in [false, true] {
+			while let Some(index) = frontier.pop() {
+				let coord = scan_coord(index);
 				if !self.is_tree_node_taken(coord) {
 					continue;
 				}
 
-				for dir in Direction::each() {
-					if let Some(&edge) = self.get_edge(coord, dir) {
-						use SpanTreeEdgeType::*;
-						// The edge is free if it's free or it's covered and we're overriding covered edges.
-						let free = edge == Free || (allow_covered && edge == CoveredByFutureSnake);
-
-						if free {
-							let seed_coord = coord.go_towards(dir);
-							// Check if the node is actually taken
-							if !self.is_tree_node_taken(seed_coord) {
-								// Seed the tree if all the conditions are met
-								self.seed_tree_from(coord, dir);
+				// Find the first edge that leads to an untaken node
+				let seed_dir = Direction::each().find(|&dir| {
+					use SpanTreeEdgeType::*;
+					// The edge is free if it's free or it's covered and we're overriding covered edges.
+					let free = match self.get_edge(coord, dir) {
+						Some(&edge) => {
+							edge == Free || (allow_covered && edge == CoveredByFutureSnake)
+						}
+						None => false,
+					};
 
-								if allow_covered {
-									seeded_covered_edge = true;
-								}
+					free && !self.is_tree_node_taken(coord.go_towards(dir))
+				});
 
-								continue 'iter;
-							}
-						}
+				let Some(dir) = seed_dir else {
+					// Nodes that still border untaken nodes may be able to seed over covered edges
+					if !allow_covered && self.borders_untaken_node(coord) {
+						next_pass.push(index);
 					}
+					continue;
+				};
+
+				self.seed_tree_from(coord, dir, &mut left_behind);
+				seeded_covered_edge |= allow_covered;
+
+				// The seed may border more regions, as may the nodes the search left behind
+				frontier.push(index);
+				for coord in left_behind.drain(..) {
+					frontier.push(scan_index(coord));
 				}
 			}
 
-			// We iterate twice, once without allowing covered edges, and once with.
-			if !allow_covered {
-				allow_covered = true;
-				continue;
-			} else {
-				break;
+			for index in next_pass.drain(..) {
+				frontier.push(index);
 			}
 		}
 
@@ -137,20 +202,102 @@ impl SpanningTree {
 		}
 	}
 
-	/// Seed the tree from a node and a direction. This begins a depth first search minimum spanning
-	/// tree seeding process, and fills all the available space in the region.
-	fn seed_tree_from(&mut self, coord: Coord, dir: Direction) {
+	/// A snake that fits inside a single node doesn't take any nodes, so start the tree
+	/// from the first free edge instead.
+	fn seed_untaken_tree(&mut self) {
+		if self
+			.iter_all_coords()
+			.any(|coord| self.is_tree_node_taken(coord))
+		{
+			return;
+		}
+
+		let first_edge = self.iter_all_coords().find_map(|coord| {
+			Direction::each()
+				.find(|&dir| self.get_edge(coord, dir) == Some(&SpanTreeEdgeType::Free))
+				.map(|dir| (coord, dir))
+		});
+
+		if let Some((coord, dir)) = first_edge {
+			self.seed_tree_from(coord, dir, &mut Vec::new());
+		}
+	}
+
+	/// Check if any of a node's neighbours aren't taken yet.
+	fn borders_untaken_node(&self, coord: Coord) -> bool {
+		Direction::each().any(|dir| self.is_untaken_node(coord.go_towards(dir)))
+	}
+
+	/// Check if a node is inside the grid and not taken yet.
+	fn is_untaken_node(&self, coord: Coord) -> bool {
+		self.is_in_bounds(coord) && !self.is_tree_node_taken(coord)
+	}
+
+	/// Remove all of a node's tree edges, so that the tree can be grown into it again.
+	pub(crate) fn untake_node(&mut self, coord: Coord) {
+		for dir in Direction::each() {
+			if self.get_edge(coord, dir) == Some(&SpanTreeEdgeType::Wall) {
+				self.set_edge(coord, dir, SpanTreeEdgeType::Free);
+			}
+		}
+	}
+
+	/// Mark an edge that the planned path moves clockwise across, remembering it so that
+	/// it can be cleared again once the path has been followed. Edges that are already
+	/// walls or covered by the current snake are left as they are.
+	pub(crate) fn mark_covered_by_future_snake(&mut self, coord: Coord, dir: Direction) {
+		use SpanTreeEdgeType::*;
+		if matches!(
+			self.get_edge(coord, dir),
+			Some(Wall | CoveredByCurrentSnake)
+		) {
+			return;
+		}
+
+		if self.try_set_edge(coord, dir, SpanTreeEdgeType::CoveredByFutureSnake) {
+			self.future_edges.push((coord, dir));
+		}
+	}
+
+	/// Free all the edges marked by `mark_covered_by_future_snake` that weren't grown over.
+	pub(crate) fn clear_future_edges(&mut self) {
+		for (coord, dir) in self.future_edges.drain(..) {
+			if self.graph.get_edge(coord, dir) == Some(&SpanTreeEdgeType::CoveredByFutureSnake) {
+				self.graph.set_edge(coord, dir, SpanTreeEdgeType::Free);
+			}
+		}
+	}
+
+	/// Seed the tree from a node and a direction, growing into the untaken region on the other
+	/// side of the edge with the tree's growth strategy. Nodes that may still border untaken
+	/// nodes afterwards are added to `left_behind` so that they can seed the rest of the region.
+	fn seed_tree_from(&mut self, coord: Coord, dir: Direction, left_behind: &mut Vec<Coord>) {
 		self.set_edge(coord, dir, SpanTreeEdgeType::Wall);
 
+		let start = coord.go_towards(dir);
+		match self.growth_strategy {
+			TreeGrowthStrategy::RandomDfs => self.grow_random_dfs(start, left_behind),
+			TreeGrowthStrategy::RandomizedPrim => self.grow_randomized_prim(start),
+			TreeGrowthStrategy::Wilson => self.grow_wilson(start),
+			TreeGrowthStrategy::Compact => self.grow_compact(start),
+		}
+	}
+
+	/// Grow a depth first search minimum spanning tree from a taken node. Nodes that the search
+	/// backtracks to are dropped from its stack, so it can leave parts of the region unfilled next
+	/// to them. Those nodes are added to `left_behind`.
+	fn grow_random_dfs(&mut self, start: Coord, left_behind: &mut Vec<Coord>) {
 		// Leave a small vector for caching directions later
 		let mut possible_dirs = Vec::with_capacity(4);
 
-		let mut last_coord = coord.go_towards(dir);
+		let mut last_coord = start;
 
 		// We track the depth first search previous locations with a stack
 		let mut stack = VecDeque::new();
 		stack.push_back(last_coord);
 
+		let mut backtracked = false;
+
 		loop {
 			let current_coord = last_coord;
 
@@ -167,16 +314,23 @@ impl SpanningTree {
 			}
 
 			// If we reached a dead end, step backwards
-			if possible_dirs.len() == 0 {
+			if possible_dirs.is_empty() {
 				last_coord = match stack.pop_back() {
 					Some(coord) => coord,
 					None => break,
 				};
+				backtracked = true;
 				continue;
 			}
 
+			// This node is no longer on the stack, so the search won't come back to it
+			if backtracked {
+				left_behind.push(current_coord);
+				backtracked = false;
+			}
+
 			// Pick a random direction from the list
-			let dir = possible_dirs[rand::thread_rng().gen_range(0..possible_dirs.len())];
+			let dir = possible_dirs[crate::rng::thread_rng().gen_range(0..possible_dirs.len())];
 			// let dir = possible_dirs[0];
 			possible_dirs.clear();
 
@@ -189,6 +343,121 @@ impl SpanningTree {
 		}
 	}
 
+	/// Grow the tree from a taken node by repeatedly taking a random edge from the tree to an
+	/// untaken node, which gives short branches spreading out from the start.
+	fn grow_randomized_prim(&mut self, start: Coord) {
+		let mut frontier = Vec::new();
+		let mut newest = start;
+
+		loop {
+			// Add the edges from the newest node to its untaken neighbours
+			for dir in Direction::each() {
+				if self.is_untaken_node(newest.go_towards(dir)) {
+					frontier.push((newest, dir));
+				}
+			}
+
+			// Pick random edges until one leads to a node that wasn't taken through another edge
+			loop {
+				if frontier.is_empty() {
+					return;
+				}
+
+				let index = crate::rng::thread_rng().gen_range(0..frontier.len());
+				let (coord, dir) = frontier.swap_remove(index);
+
+				let next_coord = coord.go_towards(dir);
+				if self.is_untaken_node(next_coord) {
+					self.set_edge(coord, dir, SpanTreeEdgeType::Wall);
+					newest = next_coord;
+					break;
+				}
+			}
+		}
+	}
+
+	/// Grow a uniformly random spanning tree over the region around a taken node with Wilson's
+	/// algorithm, joining each untaken node to the tree with a loop-erased random walk.
+	fn grow_wilson(&mut self, start: Coord) {
+		// Step 1: Flood fill the region, as the walks can't leave it
+		let mut region = vec![start];
+		self.region.set(start, true);
+
+		let mut i = 0;
+		while let Some(&coord) = region.get(i) {
+			for dir in Direction::each() {
+				let next_coord = coord.go_towards(dir);
+				if self.is_untaken_node(next_coord) && !self.region[next_coord] {
+					self.region.set(next_coord, true);
+					region.push(next_coord);
+				}
+			}
+			i += 1;
+		}
+
+		// Step 2: Walk randomly from each node until the tree is hit, remembering only the last
+		// direction taken from each node so that any loops are erased
+		let mut possible_dirs = Vec::with_capacity(4);
+		for &walk_start in region.iter() {
+			if self.is_tree_node_taken(walk_start) {
+				continue;
+			}
+
+			let mut current = walk_start;
+			while !self.is_tree_node_taken(current) {
+				possible_dirs.extend(Direction::each().filter(|&dir| {
+					self.region
+						.get(current.go_towards(dir))
+						.copied()
+						.unwrap_or(false)
+				}));
+
+				let dir = possible_dirs[crate::rng::thread_rng().gen_range(0..possible_dirs.len())];
+				possible_dirs.clear();
+
+				self.walk_dirs.set(current, dir);
+				current = current.go_towards(dir);
+			}
+
+			// Step 3: Add the loop-erased walk to the tree. Each wall takes the node after it too,
+			// so check whether that node was already in the tree before placing the wall
+			let mut current = walk_start;
+			loop {
+				let dir = self.walk_dirs[current];
+				let next_coord = current.go_towards(dir);
+				let reached_tree = self.is_tree_node_taken(next_coord);
+
+				self.set_edge(current, dir, SpanTreeEdgeType::Wall);
+				if reached_tree {
+					break;
+				}
+				current = next_coord;
+			}
+		}
+
+		for coord in region {
+			self.region.set(coord, false);
+		}
+	}
+
+	/// Grow the tree breadth first from a taken node, always trying directions in the same order.
+	/// Every node ends up as close to the start as the region allows, so the tree stays compact
+	/// around the path it was seeded from rather than winding away from it.
+	fn grow_compact(&mut self, start: Coord) {
+		let mut queue = VecDeque::new();
+		queue.push_back(start);
+
+		while let Some(coord) = queue.pop_front() {
+			for dir in Direction::each() {
+				let next_coord = coord.go_towards(dir);
+				if self.is_untaken_node(next_coord) {
+					self.set_edge(coord, dir, SpanTreeEdgeType::Wall);
+					queue.push_back(next_coord);
+				}
+			}
+		}
+	}
+
 	/// Check if the snake can pathfind from the current cell outwards. It can can't do it if the next
 	/// node is taken and the connecting edge isn't, which implies that the new connection may cause a loop.
 	pub fn can_walk_out_from(&self, coord: Coord) -> bool {
@@ -215,24 +484,22 @@ impl SpanningTree {
 			return true;
 		}
 
-		return false;
+		false
 	}
 
-	// Given a coordinate, check if the there's an edge blocking clockwise motion
+	/// Given a coordinate, check if the there's an edge blocking clockwise motion.
 	pub fn can_walk_clockwise_from(&self, coord: Coord) -> bool {
 		let [clockwise, _] = get_valid_dirs_from_coord(coord);
 		let (coord, dir) = calculate_inner_tree_coord(coord, clockwise);
 
 		let connecting_edge = self.graph.get_edge(coord, dir);
-		let result = match connecting_edge {
+		match connecting_edge {
 			Some(edge) => edge.is_free(),
 			_ => true,
-		};
-
-		result
+		}
 	}
 
-	// Given a node's coordinate, check if it has any taken edges (signifying that the node itself is taken)
+	/// Given a node's coordinate, check if it has any taken edges (signifying that the node itself is taken).
 	pub fn is_tree_node_taken(&self, coord: Coord) -> bool {
 		for dir in Direction::each() {
 			if let Some(edge) = self.get_edge(coord, dir) {
@@ -248,7 +515,15 @@ impl SpanningTree {
 	/// Starting from the snake's head and ending at the snake's food, follow the clockwise
 	/// stepping rules until we reach the food. Return the final path.
 	pub fn build_snake_path(&self, world: &SnakeWorld) -> Path {
+		self.try_build_snake_path(world)
+			.expect("The food isn't reachable along the spanning tree")
+	}
+
+	/// Like `build_snake_path`, but returns `None` if the food isn't reached after stepping
+	/// over every cell, which happens when the tree doesn't span the whole grid.
+	pub fn try_build_snake_path(&self, world: &SnakeWorld) -> Option<Path> {
 		let mut current = world.snake_head_coord();
+		let max_len = world.size() * world.size();
 
 		let mut path = Path::new();
 		loop {
@@ -257,24 +532,148 @@ impl SpanningTree {
 				break;
 			}
 
-			let [clockwise, out] = get_valid_dirs_from_coord(current);
+			if path.len() >= max_len {
+				return None;
+			}
+
+			// Navigate towards the next direction on the cycle
+			let next_dir = self.next_dir_on_cycle(current);
+			current = current.go_towards(next_dir);
+			path.push(next_dir);
+		}
+
+		Some(path)
+	}
 
-			let (coord, dir) = calculate_following_out_edge(current);
-			let out_edge = self.get_edge(coord, dir);
+	/// The direction the cycle around the tree takes from a cell of the snake world.
+	pub fn next_dir_on_cycle(&self, coord: Coord) -> Direction {
+		let [clockwise, out] = get_valid_dirs_from_coord(coord);
 
-			// If the outwards edge is taken then we go out, otherwise we can go clockwise
-			let next_dir = if let Some(&SpanTreeEdgeType::Wall) = out_edge {
-				out
-			} else {
-				clockwise
-			};
+		let (coord, dir) = calculate_following_out_edge(coord);
+		let out_edge = self.get_edge(coord, dir);
 
-			// Navigate towards the chosen direction
+		// If the outwards edge is taken then we go out, otherwise we can go clockwise
+		if let Some(&SpanTreeEdgeType::Wall) = out_edge {
+			out
+		} else {
+			clockwise
+		}
+	}
+
+	/// Follow the cycle around the tree from a cell of the snake world until it returns to that
+	/// cell, returning the whole loop. Returns `None` if the loop doesn't pass over every cell,
+	/// which happens when the tree doesn't span the whole grid.
+	pub fn build_cycle_path(&self, from: Coord) -> Option<Path> {
+		let cell_count = self.size() * self.size() * 4;
+
+		let mut current = from;
+		let mut path = Path::new();
+		while path.len() < cell_count {
+			let next_dir = self.next_dir_on_cycle(current);
 			current = current.go_towards(next_dir);
 			path.push(next_dir);
+
+			if current == from {
+				break;
+			}
 		}
 
-		path
+		(current == from && path.len() == cell_count).then_some(path)
+	}
+
+	/// Follow the cycle around the tree from a cell of the snake world, collecting every cell it
+	/// passes over and their positions in the cycle.
+	pub fn hamiltonian_cycle(&self, from: Coord) -> HamiltonianCycle {
+		HamiltonianCycle::trace(self.size() * 2, from, |coord| self.next_dir_on_cycle(coord))
+	}
+
+	/// Shorten the path from the snake's head to the food by moving the subtrees that it walks
+	/// all the way around to the other side of the food. Each move swaps one of the tree's walls
+	/// for another, which flips two 2x2 blocks of cells in the cycle, so the cycle still covers
+	/// every cell and the snake's body still follows it. Returns how many steps were removed.
+	///
+	/// Only subtrees that border a cell past the food can be moved. The subtrees around a path
+	/// planned on a freshly built tree are enclosed by that path, so this only shortens paths
+	/// through trees that were kept from earlier paths.
+	pub fn remove_detours(&mut self, world: &SnakeWorld) -> usize {
+		// Step 1: Index the cells of the current path, from the head to the food
+		let mut path_cells = std::mem::take(&mut self.path_cells);
+		let max_len = world.size() * world.size();
+
+		let mut current = world.snake_head_coord();
+		path_cells.push(current);
+		while current != world.food_coord() && path_cells.len() <= max_len {
+			current = current.go_towards(self.next_dir_on_cycle(current));
+			path_cells.push(current);
+		}
+
+		for (index, &coord) in path_cells.iter().enumerate() {
+			self.path_index.set(coord, index as u32);
+		}
+
+		// Step 2: Look for walls that lead from the path into a subtree that the path leaves
+		// through the same wall, before reaching the food
+		let food_index = path_cells.len() - 1;
+		let mut removed = 0;
+		let mut index = 0;
+		while current == world.food_coord() && index < food_index {
+			let (node, dir) = calculate_following_out_edge(path_cells[index]);
+			if self.get_edge(node, dir) != Some(&SpanTreeEdgeType::Wall) {
+				index += 1;
+				continue;
+			}
+
+			let return_cell = calculate_out_cell(node.go_towards(dir), dir.opposite());
+			let return_index = self.path_index[return_cell] as usize;
+			if return_index <= index || return_index >= food_index {
+				index += 1;
+				continue;
+			}
+
+			// Step 3: Find an edge that reattaches the subtree to a cell past the food, which
+			// the snake's body doesn't cover
+			let replacement = path_cells[index + 1..=return_index]
+				.iter()
+				.find_map(|&cell| {
+					let (subtree_node, subtree_dir) = calculate_following_out_edge(cell);
+
+					use SpanTreeEdgeType::*;
+					let Some(Free | CoveredByFutureSnake) =
+						self.get_edge(subtree_node, subtree_dir)
+					else {
+						return None;
+					};
+
+					let outside_cell = calculate_out_cell(
+						subtree_node.go_towards(subtree_dir),
+						subtree_dir.opposite(),
+					);
+					let past_food = self
+						.path_index
+						.get(outside_cell)
+						.is_some_and(|&index| index as usize >= food_index);
+					let covered = matches!(world.get_cell(outside_cell), Some(Cell::Snake(_)));
+
+					(past_food && !covered).then_some((subtree_node, subtree_dir))
+				});
+
+			// Step 4: Swap the walls, which moves the whole subtree's loop to after the food.
+			// Subtrees nested inside this one can't be moved if it can't, so skip past it either way.
+			if let Some((subtree_node, subtree_dir)) = replacement {
+				self.set_edge(node, dir, SpanTreeEdgeType::Free);
+				self.set_edge(subtree_node, subtree_dir, SpanTreeEdgeType::Wall);
+				removed += return_index - index;
+			}
+
+			index = return_index + 1;
+		}
+
+		for coord in path_cells.drain(..) {
+			self.path_index.set(coord, u32::MAX);
+		}
+		self.path_cells = path_cells;
+
+		removed
 	}
 
 	/// Convert the minimum spanning tree into a collision grid. This was used in the past to
@@ -311,8 +710,28 @@ impl SpanningTree {
 		snake_grid
 	}
 
+	/// The graph of the tree's edges, where each cell is a node of the tree.
+	pub fn edges(&self) -> &GridGraph<SpanTreeEdgeType> {
+		&self.graph
+	}
+
+	pub(crate) fn set_edge(&mut self, coord: Coord, dir: Direction, edge: SpanTreeEdgeType) {
+		self.graph.set_edge(coord, dir, edge);
+	}
+
+	pub(crate) fn try_set_edge(
+		&mut self,
+		coord: Coord,
+		dir: Direction,
+		edge: SpanTreeEdgeType,
+	) -> bool {
+		self.graph.try_set_edge(coord, dir, edge)
+	}
+
+	/// Remove every edge from the tree.
 	pub fn clear(&mut self) {
 		self.graph.fill(SpanTreeEdgeType::Free);
+		self.future_edges.clear();
 	}
 }
 
@@ -324,8 +743,158 @@ impl std::ops::Deref for SpanningTree {
 	}
 }
 
-impl std::ops::DerefMut for SpanningTree {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.graph
+/// Nodes waiting to seed new regions, handed out smallest scan index first. Each node is queued
+/// at most once, and popping scans forward from the smallest node queued since the last pop.
+struct ScanFrontier {
+	queued: Vec<bool>,
+	cursor: usize,
+}
+
+impl ScanFrontier {
+	fn new(len: usize) -> Self {
+		Self {
+			queued: vec![false; len],
+			cursor: len,
+		}
+	}
+
+	fn push(&mut self, index: usize) {
+		self.queued[index] = true;
+		self.cursor = self.cursor.min(index);
+	}
+
+	fn pop(&mut self) -> Option<usize> {
+		let offset = self.queued[self.cursor..]
+			.iter()
+			.position(|&queued| queued)?;
+		self.cursor += offset;
+		self.queued[self.cursor] = false;
+		Some(self.cursor)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		rng::seed_thread_rng,
+		snake::{SnakeResult, SnakeWorld},
+		solvers::{
+			snake_spanning_tree::{
+				pathfinding::{pathfind_on_spanning_tree, PathfindingGrid, SnakePathfindResult},
+				JitterKind, SnakeSpanningTreeSolver, TreeGrowthStrategy,
+			},
+			SnakeSolver,
+		},
+	};
+
+	use super::*;
+
+	/// Grow the tree by scanning the whole grid again after every seed, the way the frontier
+	/// in `grow_spanning_tree_from` is meant to order its seeds.
+	fn grow_by_rescanning(tree: &mut SpanningTree) -> SnakeGrowResult {
+		tree.seed_untaken_tree();
+
+		let mut seeded_covered_edge = false;
+		for allow_covered in [false, true] {
+			'scan: loop {
+				for coord in tree.iter_all_coords() {
+					if !tree.is_tree_node_taken(coord) {
+						continue;
+					}
+
+					for dir in Direction::each() {
+						use SpanTreeEdgeType::*;
+						let Some(&edge) = tree.get_edge(coord, dir) else {
+							continue;
+						};
+
+						let free = edge == Free || (allow_covered && edge == CoveredByFutureSnake);
+						if free && !tree.is_tree_node_taken(coord.go_towards(dir)) {
+							tree.seed_tree_from(coord, dir, &mut Vec::new());
+							seeded_covered_edge |= allow_covered;
+							continue 'scan;
+						}
+					}
+				}
+
+				break;
+			}
+		}
+
+		if seeded_covered_edge {
+			SnakeGrowResult::SuccessWithPathOverride
+		} else {
+			SnakeGrowResult::Success
+		}
+	}
+
+	/// Trace the snake and pathfind to the food, leaving the tree ready to grow.
+	fn tree_before_growth(
+		world: &SnakeWorld,
+		strategy: TreeGrowthStrategy,
+	) -> Option<SpanningTree> {
+		let mut tree = SpanningTree::new(world.size(), strategy);
+		let mut grid = PathfindingGrid::new(world.size());
+		tree.trace_current_snake_and_mark_edges(world);
+		grid.fill_pathfinding_grid(world, &tree);
+		let result = pathfind_on_spanning_tree(world.snake_head_coord(), &grid, &mut tree);
+		(result == SnakePathfindResult::Success).then_some(tree)
+	}
+
+	#[test]
+	fn frontier_growth_matches_rescanning() {
+		let strategies = [
+			TreeGrowthStrategy::RandomDfs,
+			TreeGrowthStrategy::RandomizedPrim,
+			TreeGrowthStrategy::Wilson,
+			TreeGrowthStrategy::Compact,
+		];
+
+		let mut compared = 0;
+		for seed in 0..20 {
+			seed_thread_rng(seed);
+			let mut world = SnakeWorld::new(12);
+			let mut solver = SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10));
+
+			'game: for _ in 0..30 {
+				for (i, &strategy) in strategies.iter().enumerate() {
+					let (Some(mut frontier), Some(mut rescanned)) = (
+						tree_before_growth(&world, strategy),
+						tree_before_growth(&world, strategy),
+					) else {
+						continue;
+					};
+
+					let grow_seed = seed * 1000 + compared as u64 + i as u64;
+					seed_thread_rng(grow_seed);
+					let frontier_result = frontier.grow_spanning_tree();
+					seed_thread_rng(grow_seed);
+					let rescanned_result = grow_by_rescanning(&mut rescanned);
+
+					assert_eq!(frontier_result, rescanned_result, "seed {}", seed);
+					for coord in frontier.iter_all_coords() {
+						for dir in Direction::each() {
+							assert_eq!(
+								frontier.get_edge(coord, dir),
+								rescanned.get_edge(coord, dir),
+								"seed {}, {:?} {:?}",
+								seed,
+								coord,
+								dir
+							);
+						}
+					}
+					compared += 1;
+				}
+
+				for dir in solver.get_next_path(&world).iter_directions() {
+					if world.step_snake(dir) != SnakeResult::Stepped {
+						break 'game;
+					}
+				}
+			}
+		}
+
+		assert!(compared > 1000);
 	}
 }
@@ -20,6 +20,17 @@ pub fn get_valid_dirs_from_coord(coord: Coord) -> [Direction; 2] {
 	[clockwise, out]
 }
 
+/// The direction the cycle around a boolean collision grid's walls takes from a cell.
+pub fn next_dir_on_collision_grid(grid: &GridGraph<bool>, coord: Coord) -> Direction {
+	let [clockwise, out] = get_valid_dirs_from_coord(coord);
+
+	if grid.get_edge(coord, clockwise) == Some(&false) {
+		clockwise
+	} else {
+		out
+	}
+}
+
 /// Build a snake path from a boolean collision grid, starting from the snake head until it reaches the food.
 pub fn build_path_from_collision_grid(grid: &GridGraph<bool>, world: &SnakeWorld) -> Path {
 	let mut current = world.snake_head_coord();
@@ -30,13 +41,7 @@ pub fn build_path_from_collision_grid(grid: &GridGraph<bool>, world: &SnakeWorld
 			break;
 		}
 
-		let [clockwise, out] = get_valid_dirs_from_coord(current);
-
-		let next_dir = if grid.get_edge(current, clockwise) == Some(&false) {
-			clockwise
-		} else {
-			out
-		};
+		let next_dir = next_dir_on_collision_grid(grid, current);
 
 		current = current.go_towards(next_dir);
 		path.push(next_dir);
@@ -44,3 +49,21 @@ pub fn build_path_from_collision_grid(grid: &GridGraph<bool>, world: &SnakeWorld
 
 	path
 }
+
+/// Check if the snake's body, from the tail to the head, is a stretch of a cycle, given the cell
+/// the cycle moves to from each cell.
+pub fn body_follows_cycle(
+	world: &SnakeWorld,
+	next_on_cycle: impl Fn(Coord) -> Option<Coord>,
+) -> bool {
+	let mut current = world.snake_head_coord();
+	for dir in world.calculate_snake_path_from_head().iter_directions() {
+		let prev = current.go_towards(dir);
+		if next_on_cycle(prev) != Some(current) {
+			return false;
+		}
+		current = prev;
+	}
+
+	true
+}
@@ -0,0 +1,182 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+	auto::Path,
+	snake::SnakeWorld,
+	ui::{SnakeWorldViewer, TerminalColors},
+	Coord,
+};
+
+use super::{hamiltonian_cycle::HamiltonianCycle, SnakeSolver};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PathViolationKind {
+	/// The solver returned a path with no steps.
+	EmptyPath,
+	/// The step moves the head outside of the world.
+	OutOfBounds,
+	/// The step moves the head into a body cell that won't have moved out of the way yet.
+	BodyCollision,
+	/// The step eats the food before the end of the path, after which the rest of the path is unpredictable.
+	EatsFoodEarly,
+	/// The path ends without reaching the food.
+	DoesNotReachFood,
+}
+
+/// The first offending step of an invalid path, along with a snapshot of the world it was planned for.
+#[derive(Clone)]
+pub struct PathViolation {
+	pub kind: PathViolationKind,
+	/// The index of the offending step in the path.
+	pub step: usize,
+	/// The coordinate the head would be moved to by the offending step.
+	pub coord: Coord,
+	pub path: Path,
+	pub world: SnakeWorld,
+}
+
+impl Display for PathViolation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		writeln!(
+			f,
+			"Invalid path: {:?} at step {} of {} (moving to {:?}), head at {:?}, food at {:?}, length {}",
+			self.kind,
+			self.step,
+			self.path.len(),
+			self.coord,
+			self.world.snake_head_coord(),
+			self.world.food_coord(),
+			self.world.snake_length()
+		)?;
+
+		let snapshot = SnakeWorldViewer::new(&self.world)
+			.with_path_overlay(&self.path)
+			.render_terminal(TerminalColors::Plain);
+		write!(f, "{}", snapshot)
+	}
+}
+
+impl std::fmt::Debug for PathViolation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(self, f)
+	}
+}
+
+/// Check that a path can be followed in the world without killing the snake. If `require_food`
+/// is true then the path must also end on the food, otherwise the path may stop short of it.
+pub fn validate_path(
+	world: &SnakeWorld,
+	path: &Path,
+	require_food: bool,
+) -> Result<(), PathViolation> {
+	let violation = |kind, step, coord| PathViolation {
+		kind,
+		step,
+		coord,
+		path: path.clone(),
+		world: world.clone(),
+	};
+
+	if path.is_empty() {
+		return Err(violation(
+			PathViolationKind::EmptyPath,
+			0,
+			world.snake_head_coord(),
+		));
+	}
+
+	// The forecast stops at the first step that leaves the world or runs into the body
+	let forecast = world.forecast_occupancy(path);
+	let mut current = world.snake_head_coord();
+
+	for (index, dir) in path.iter_directions().enumerate() {
+		current = current.go_towards(dir);
+
+		if index == forecast.safe_steps() {
+			let kind = match forecast.free_after(current) {
+				None => PathViolationKind::OutOfBounds,
+				Some(_) => PathViolationKind::BodyCollision,
+			};
+			return Err(violation(kind, index, current));
+		}
+
+		if current == world.food_coord() && index + 1 != path.len() {
+			return Err(violation(PathViolationKind::EatsFoodEarly, index, current));
+		}
+	}
+
+	if require_food && current != world.food_coord() {
+		return Err(violation(
+			PathViolationKind::DoesNotReachFood,
+			path.len() - 1,
+			current,
+		));
+	}
+
+	Ok(())
+}
+
+/// Wraps a solver and checks every path it returns against the world before it is executed.
+/// By default the first invalid path panics with a report of the offending step and a snapshot
+/// of the world, but violations can be recorded instead with `record_violations`.
+pub struct ValidatingSolver<S: SnakeSolver> {
+	pub solver: S,
+	require_food: bool,
+	panic_on_violation: bool,
+	violations: Vec<PathViolation>,
+}
+
+impl<S: SnakeSolver> ValidatingSolver<S> {
+	pub fn new(solver: S) -> Self {
+		Self {
+			solver,
+			require_food: true,
+			panic_on_violation: true,
+			violations: Vec::new(),
+		}
+	}
+
+	/// Allow paths that stop before reaching the food, for solvers that shorten their paths (e.g. with jitter).
+	pub fn allow_partial_paths(mut self) -> Self {
+		self.require_food = false;
+		self
+	}
+
+	/// Record violations instead of panicking. Invalid paths are still returned to the caller.
+	pub fn record_violations(mut self) -> Self {
+		self.panic_on_violation = false;
+		self
+	}
+
+	/// The violations recorded so far, one for each invalid path.
+	pub fn violations(&self) -> &[PathViolation] {
+		&self.violations
+	}
+}
+
+impl<S: SnakeSolver> SnakeSolver for ValidatingSolver<S> {
+	fn get_next_path(&mut self, world: &SnakeWorld) -> Path {
+		let path = self.solver.get_next_path(world);
+
+		if let Err(violation) = validate_path(world, &path, self.require_food) {
+			if self.panic_on_violation {
+				panic!("{}", violation);
+			}
+			self.violations.push(violation);
+		}
+
+		path
+	}
+
+	fn last_path_was_overridden(&self) -> bool {
+		self.solver.last_path_was_overridden()
+	}
+
+	fn hamiltonian_cycle(&self, world: &SnakeWorld) -> Option<HamiltonianCycle> {
+		self.solver.hamiltonian_cycle(world)
+	}
+
+	fn decorate_widget<'a>(&'a self, widget: SnakeWorldViewer<'a>) -> SnakeWorldViewer<'a> {
+		self.solver.decorate_widget(widget)
+	}
+}
@@ -2,15 +2,26 @@ use eframe::egui::{self, Response, Sense, Widget};
 use maybe_owned::MaybeOwned;
 
 use crate::{
-	array2d::Array2D, auto::Path, direction::Direction, grid_graph::GridGraph, snake::SnakeWorld,
+	array2d::Array2D,
+	auto::Path,
+	direction::Direction,
+	grid_graph::GridGraph,
+	snake::{OccupancyForecast, SnakeWorld},
 	Coord,
 };
 
+mod raster;
+mod svg;
+mod terminal;
+pub use raster::{GifWriter, RgbaImage};
+pub use terminal::TerminalColors;
+
 pub struct SnakeWorldViewer<'a> {
 	snake_world: &'a SnakeWorld,
 	overlay_path: Option<MaybeOwned<'a, Path>>,
 	bools_edges_grid: Vec<(MaybeOwned<'a, GridGraph<bool>>, egui::Color32)>,
 	pathfinding_grid: Option<MaybeOwned<'a, Array2D<u32>>>,
+	occupancy_forecast: Option<MaybeOwned<'a, OccupancyForecast>>,
 }
 
 impl<'a> SnakeWorldViewer<'a> {
@@ -24,6 +35,7 @@ impl<'a> SnakeWorldViewer<'a> {
 			overlay_path: None,
 			bools_edges_grid: Vec::new(),
 			pathfinding_grid: None,
+			occupancy_forecast: None,
 		}
 	}
 
@@ -48,31 +60,44 @@ impl<'a> SnakeWorldViewer<'a> {
 		self.pathfinding_grid = Some(pathfinding_grid.into());
 		self
 	}
+
+	/// Shade the cells the snake will cover along its path, brighter for cells that stay covered for longer.
+	pub fn with_occupancy_overlay(
+		mut self,
+		occupancy_forecast: impl Into<MaybeOwned<'a, OccupancyForecast>>,
+	) -> Self {
+		self.occupancy_forecast = Some(occupancy_forecast.into());
+		self
+	}
 }
 
 const CELL_SIZE: f32 = 10.0;
 
-impl Widget for SnakeWorldViewer<'_> {
-	fn ui(self, ui: &mut eframe::egui::Ui) -> Response {
-		let size = self.snake_world.size() as f32 * CELL_SIZE;
+/// A drawing backend for `SnakeWorldViewer`. The viewer describes the world and its overlays as
+/// a list of filled rectangles followed by line segments, so that every backend draws the same picture.
+pub trait WorldPainter {
+	fn rect(&mut self, rect: egui::Rect, color: egui::Color32);
 
-		let (rect, response) = ui.allocate_exact_size(egui::vec2(size, size), Sense::click());
+	fn line_segment(&mut self, points: [egui::Pos2; 2], width: f32, color: egui::Color32);
+}
 
-		let painter = ui.painter();
+impl SnakeWorldViewer<'_> {
+	/// Paint the world and all of its overlays with the top left corner of the world at `origin`.
+	pub fn paint(&self, origin: egui::Pos2, painter: &mut impl WorldPainter) {
+		let size = self.snake_world.size() as f32 * CELL_SIZE;
+		let rect = egui::Rect::from_min_size(origin, egui::vec2(size, size));
 
 		let snake_path = self.snake_world.calculate_snake_path_from_head();
 
-		let mut mesh = egui::Mesh::default();
-
 		let get_coord_vec2 = |coord: Coord| {
 			rect.min + egui::vec2(coord.x as f32 * CELL_SIZE, coord.y as f32 * CELL_SIZE)
 		};
 		let half_cell = egui::vec2(CELL_SIZE / 2.0, CELL_SIZE / 2.0);
 
 		// Add background
-		mesh.add_colored_rect(rect, egui::Color32::from_rgb(0, 0, 0));
+		painter.rect(rect, egui::Color32::from_rgb(0, 0, 0));
 
-		if let Some(pathfinding) = self.pathfinding_grid {
+		if let Some(pathfinding) = &self.pathfinding_grid {
 			// Find the maximum value
 			let mut max_value = 0;
 			for coord in pathfinding.iter_all_coords() {
@@ -94,12 +119,39 @@ impl Widget for SnakeWorldViewer<'_> {
 					get_coord_vec2(coord),
 					egui::vec2(CELL_SIZE, CELL_SIZE),
 				);
-				mesh.add_colored_rect(rect, color);
+				painter.rect(rect, color);
+			}
+		}
+
+		if let Some(forecast) = &self.occupancy_forecast {
+			let free_after = forecast.free_after_grid();
+			let max_value = free_after
+				.iter_all_coords()
+				.map(|coord| *free_after.get(coord).unwrap())
+				.max()
+				.unwrap_or(0);
+
+			for coord in free_after.iter_all_coords() {
+				let value = *free_after.get(coord).unwrap();
+				if value == 0 {
+					continue;
+				}
+
+				let color = egui::Color32::from_rgb(
+					0,
+					0,
+					(64.0 + 191.0 * (value as f32 / max_value as f32)) as u8,
+				);
+				let rect = egui::Rect::from_min_size(
+					get_coord_vec2(coord),
+					egui::vec2(CELL_SIZE, CELL_SIZE),
+				);
+				painter.rect(rect, color);
 			}
 		}
 
 		// Add food
-		mesh.add_colored_rect(
+		painter.rect(
 			egui::Rect::from_min_size(
 				get_coord_vec2(self.snake_world.food_coord()),
 				egui::vec2(CELL_SIZE, CELL_SIZE),
@@ -107,7 +159,7 @@ impl Widget for SnakeWorldViewer<'_> {
 			egui::Color32::from_rgb(255, 0, 0),
 		);
 
-		let mut iter = snake_path.iter_offsets();
+		let iter = snake_path.iter_offsets();
 		let head = self.snake_world.snake_head_coord();
 		let mut prev: Option<Coord> = None;
 
@@ -119,7 +171,7 @@ impl Widget for SnakeWorldViewer<'_> {
 			)
 		};
 
-		while let Some(offset) = iter.next() {
+		for offset in iter {
 			let coord = head + offset;
 
 			let rect = if let Some(prev) = prev {
@@ -128,25 +180,23 @@ impl Widget for SnakeWorldViewer<'_> {
 				get_rect_for_coord(coord)
 			};
 
-			mesh.add_colored_rect(rect, egui::Color32::from_rgb(0, 255, 0));
+			painter.rect(rect, egui::Color32::from_rgb(0, 255, 0));
 
 			prev = Some(coord);
 		}
 
-		mesh.add_colored_rect(
+		painter.rect(
 			get_rect_for_coord(self.snake_world.snake_head_coord()),
 			egui::Color32::from_rgb(0, 128, 0),
 		);
 
-		painter.add(egui::Shape::Mesh(mesh));
-
-		let render_path = |path: &Path, head: Coord, color: egui::Color32| {
+		let mut render_path = |path: &Path, head: Coord, color: egui::Color32| {
 			let mut iter = path.iter_offsets();
 
 			// Unwrap is safe here because the offsets iterator always starts with zero
 			let mut prev = iter.next().unwrap();
 
-			while let Some(offset) = iter.next() {
+			for offset in iter {
 				let start = head + prev;
 				let end = head + offset;
 
@@ -157,10 +207,7 @@ impl Widget for SnakeWorldViewer<'_> {
 					+ egui::vec2(end.x as f32 * CELL_SIZE, end.y as f32 * CELL_SIZE)
 					+ half_cell;
 
-				painter.add(egui::Shape::line_segment(
-					[start, end],
-					egui::Stroke::new(1.0, color),
-				));
+				painter.line_segment([start, end], 1.0, color);
 
 				prev = offset;
 			}
@@ -190,14 +237,46 @@ impl Widget for SnakeWorldViewer<'_> {
 
 						let start = get_coord_vec2(start);
 						let end = get_coord_vec2(end);
-						painter.add(egui::Shape::line_segment(
-							[start, end],
-							egui::Stroke::new(1.0, *color),
-						));
+						painter.line_segment([start, end], 1.0, *color);
 					}
 				}
 			}
 		}
+	}
+}
+
+/// Collects the viewer's rectangles into a single mesh, and the lines into separate shapes.
+#[derive(Default)]
+struct EguiPainter {
+	mesh: egui::Mesh,
+	lines: Vec<egui::Shape>,
+}
+
+impl WorldPainter for EguiPainter {
+	fn rect(&mut self, rect: egui::Rect, color: egui::Color32) {
+		self.mesh.add_colored_rect(rect, color);
+	}
+
+	fn line_segment(&mut self, points: [egui::Pos2; 2], width: f32, color: egui::Color32) {
+		self.lines.push(egui::Shape::line_segment(
+			points,
+			egui::Stroke::new(width, color),
+		));
+	}
+}
+
+impl Widget for SnakeWorldViewer<'_> {
+	fn ui(self, ui: &mut eframe::egui::Ui) -> Response {
+		let size = self.snake_world.size() as f32 * CELL_SIZE;
+
+		let (rect, response) = ui.allocate_exact_size(egui::vec2(size, size), Sense::click());
+
+		let mut egui_painter = EguiPainter::default();
+		self.paint(rect.min, &mut egui_painter);
+
+		let painter = ui.painter();
+		painter.add(egui::Shape::Mesh(egui_painter.mesh));
+		painter.extend(egui_painter.lines);
 
 		response
 	}
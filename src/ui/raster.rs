@@ -0,0 +1,207 @@
+use std::{fs::File, io, io::BufWriter, io::Write, path::Path};
+
+use eframe::egui::{self, Color32};
+
+use super::{SnakeWorldViewer, WorldPainter, CELL_SIZE};
+
+/// A simple 8 bit RGBA image, used for exporting worlds without a display.
+#[derive(Clone)]
+pub struct RgbaImage {
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			pixels: vec![0; width as usize * height as usize * 4],
+		}
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// The raw pixel data, in rows of RGBA bytes.
+	pub fn pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+
+	pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color32> {
+		if x >= self.width || y >= self.height {
+			return None;
+		}
+
+		let index = (y as usize * self.width as usize + x as usize) * 4;
+		let p = &self.pixels[index..index + 4];
+		Some(Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]))
+	}
+
+	/// Blend a color over a pixel. Out of bounds pixels are ignored.
+	pub fn blend_pixel(&mut self, x: i64, y: i64, color: Color32) {
+		if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+			return;
+		}
+
+		let index = (y as usize * self.width as usize + x as usize) * 4;
+		let pixel = &mut self.pixels[index..index + 4];
+
+		// Colors are premultiplied, so blending is a straightforward "over" operation
+		let inv_alpha = 255 - color.a() as u32;
+		let src = [color.r(), color.g(), color.b(), color.a()];
+		for (dst, src) in pixel.iter_mut().zip(src) {
+			*dst = (src as u32 + (*dst as u32 * inv_alpha) / 255) as u8;
+		}
+	}
+
+	pub fn write_png(&self, writer: impl Write) -> io::Result<()> {
+		let mut encoder = png::Encoder::new(writer, self.width, self.height);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+
+		let mut writer = encoder.write_header()?;
+		writer.write_image_data(&self.pixels)?;
+		writer.finish()?;
+
+		Ok(())
+	}
+
+	pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+		self.write_png(BufWriter::new(File::create(path)?))
+	}
+}
+
+/// Rasterizes the viewer's shapes onto an `RgbaImage`, scaling every cell to `scale` times its on-screen size.
+struct RasterPainter {
+	image: RgbaImage,
+	scale: f32,
+}
+
+impl WorldPainter for RasterPainter {
+	fn rect(&mut self, rect: egui::Rect, color: Color32) {
+		let min_x = (rect.min.x * self.scale).round() as i64;
+		let min_y = (rect.min.y * self.scale).round() as i64;
+		let max_x = (rect.max.x * self.scale).round() as i64;
+		let max_y = (rect.max.y * self.scale).round() as i64;
+
+		for y in min_y..max_y {
+			for x in min_x..max_x {
+				self.image.blend_pixel(x, y, color);
+			}
+		}
+	}
+
+	fn line_segment(&mut self, points: [egui::Pos2; 2], width: f32, color: Color32) {
+		let [start, end] = points.map(|p| p.to_vec2() * self.scale);
+		let thickness = (width * self.scale).round().max(1.0) as i64;
+
+		// Walk along the line one pixel at a time, stamping a square brush at each point
+		let delta = end - start;
+		let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as i64;
+
+		let mut last = None;
+		for i in 0..=steps {
+			let point = start + delta * (i as f32 / steps as f32);
+
+			// Lines on the right and bottom edges of the image are pulled back inside it
+			let x = (point.x.floor() as i64).min(self.image.width as i64 - 1);
+			let y = (point.y.floor() as i64).min(self.image.height as i64 - 1);
+
+			if last == Some((x, y)) {
+				continue;
+			}
+			last = Some((x, y));
+
+			let offset = (thickness - 1) / 2;
+			for by in 0..thickness {
+				for bx in 0..thickness {
+					self.image
+						.blend_pixel(x - offset + bx, y - offset + by, color);
+				}
+			}
+		}
+	}
+}
+
+impl SnakeWorldViewer<'_> {
+	/// Rasterize the world and all of its overlays into an image, with each cell being
+	/// `scale` times as large as it is in the egui viewer.
+	pub fn render_image(&self, scale: u32) -> RgbaImage {
+		let size = (self.snake_world.size() as f32 * CELL_SIZE) as u32 * scale;
+
+		let mut painter = RasterPainter {
+			image: RgbaImage::new(size, size),
+			scale: scale as f32,
+		};
+		self.paint(egui::Pos2::ZERO, &mut painter);
+
+		painter.image
+	}
+}
+
+/// Writes a sequence of equally sized images as an infinitely looping animated GIF.
+pub struct GifWriter<W: Write> {
+	encoder: gif::Encoder<W>,
+	width: u16,
+	height: u16,
+	frame_delay: u16,
+}
+
+impl<W: Write> GifWriter<W> {
+	/// Create a new GIF writer. `frame_delay` is the time between frames in hundredths of a second.
+	pub fn new(writer: W, width: u32, height: u32, frame_delay: u16) -> io::Result<Self> {
+		let width = u16::try_from(width).map_err(io::Error::other)?;
+		let height = u16::try_from(height).map_err(io::Error::other)?;
+
+		let mut encoder =
+			gif::Encoder::new(writer, width, height, &[]).map_err(io::Error::other)?;
+		encoder
+			.set_repeat(gif::Repeat::Infinite)
+			.map_err(io::Error::other)?;
+
+		Ok(Self {
+			encoder,
+			width,
+			height,
+			frame_delay,
+		})
+	}
+
+	pub fn add_frame(&mut self, image: &RgbaImage) -> io::Result<()> {
+		if image.width() != self.width as u32 || image.height() != self.height as u32 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"GIF frames must all be the same size",
+			));
+		}
+
+		let mut pixels = image.pixels().to_vec();
+		let mut frame = gif::Frame::from_rgba_speed(self.width, self.height, &mut pixels, 10);
+		frame.delay = self.frame_delay;
+
+		self.encoder.write_frame(&frame).map_err(io::Error::other)
+	}
+}
+
+impl GifWriter<BufWriter<File>> {
+	pub fn create(
+		path: impl AsRef<Path>,
+		width: u32,
+		height: u32,
+		frame_delay: u16,
+	) -> io::Result<Self> {
+		Self::new(
+			BufWriter::new(File::create(path)?),
+			width,
+			height,
+			frame_delay,
+		)
+	}
+}
@@ -0,0 +1,72 @@
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use eframe::egui::{self, Color32};
+
+use super::{SnakeWorldViewer, WorldPainter, CELL_SIZE};
+
+/// Collects the viewer's shapes as SVG elements.
+#[derive(Default)]
+struct SvgPainter {
+	elements: String,
+}
+
+fn svg_color(color: Color32) -> String {
+	format!("rgb({},{},{})", color.r(), color.g(), color.b())
+}
+
+impl WorldPainter for SvgPainter {
+	fn rect(&mut self, rect: egui::Rect, color: Color32) {
+		writeln!(
+			self.elements,
+			r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+			rect.min.x,
+			rect.min.y,
+			rect.width(),
+			rect.height(),
+			svg_color(color)
+		)
+		.unwrap();
+	}
+
+	fn line_segment(&mut self, points: [egui::Pos2; 2], width: f32, color: Color32) {
+		let [start, end] = points;
+		writeln!(
+			self.elements,
+			r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="square"/>"#,
+			start.x,
+			start.y,
+			end.x,
+			end.y,
+			svg_color(color),
+			width
+		)
+		.unwrap();
+	}
+}
+
+impl SnakeWorldViewer<'_> {
+	/// Render the world and all of its overlays as an SVG document. The document uses the same
+	/// units as the egui viewer, and is displayed at `scale` times that size.
+	pub fn render_svg(&self, scale: f32) -> String {
+		let size = self.snake_world.size() as f32 * CELL_SIZE;
+
+		let mut painter = SvgPainter::default();
+		self.paint(egui::Pos2::ZERO, &mut painter);
+
+		format!(
+			concat!(
+				r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+				"\n{}</svg>\n"
+			),
+			size * scale,
+			size * scale,
+			size,
+			size,
+			painter.elements
+		)
+	}
+
+	pub fn save_svg(&self, path: impl AsRef<Path>, scale: f32) -> io::Result<()> {
+		fs::write(path, self.render_svg(scale))
+	}
+}
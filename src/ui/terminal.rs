@@ -0,0 +1,370 @@
+use std::fmt::Write;
+
+use eframe::egui::Color32;
+
+use crate::{direction::Direction, Coord};
+
+use super::SnakeWorldViewer;
+
+/// Whether the terminal output should contain ANSI color escape codes or be plain unicode text.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TerminalColors {
+	Plain,
+	TrueColor,
+}
+
+/// How the world is laid out on the character grid.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TerminalLayout {
+	/// Each cell is two characters wide, with no room for walls between cells.
+	Compact,
+	/// Each cell is one character, with a character of space between cells for walls and connectors.
+	Lattice,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct TerminalCell {
+	ch: char,
+	fg: Option<Color32>,
+	bg: Option<Color32>,
+}
+
+impl TerminalCell {
+	const EMPTY: Self = Self {
+		ch: ' ',
+		fg: None,
+		bg: None,
+	};
+}
+
+/// A character grid that the world is drawn onto before being converted to a string.
+struct TerminalCanvas {
+	layout: TerminalLayout,
+	colors: TerminalColors,
+	width: usize,
+	/// The number of lattice corners along each side of the world.
+	corner_stride: usize,
+	cells: Vec<TerminalCell>,
+	/// Bitmask of the wall arms touching each lattice corner, used to pick box drawing characters.
+	corner_arms: Vec<u8>,
+}
+
+const ARM_UP: u8 = 1;
+const ARM_DOWN: u8 = 2;
+const ARM_LEFT: u8 = 4;
+const ARM_RIGHT: u8 = 8;
+
+impl TerminalCanvas {
+	fn new(world_size: usize, layout: TerminalLayout, colors: TerminalColors) -> Self {
+		let (width, height) = match layout {
+			TerminalLayout::Compact => (world_size * 2, world_size),
+			TerminalLayout::Lattice => (world_size * 2 + 1, world_size * 2 + 1),
+		};
+
+		Self {
+			layout,
+			colors,
+			width,
+			corner_stride: world_size + 1,
+			cells: vec![TerminalCell::EMPTY; width * height],
+			corner_arms: vec![0; (world_size + 1) * (world_size + 1)],
+		}
+	}
+
+	fn cell_mut(&mut self, x: usize, y: usize) -> &mut TerminalCell {
+		&mut self.cells[y * self.width + x]
+	}
+
+	/// Return the character positions that a world cell occupies.
+	fn cell_positions(&self, coord: Coord) -> Vec<(usize, usize)> {
+		let (x, y) = (coord.x as usize, coord.y as usize);
+		match self.layout {
+			TerminalLayout::Compact => vec![(x * 2, y), (x * 2 + 1, y)],
+			TerminalLayout::Lattice => vec![(x * 2 + 1, y * 2 + 1)],
+		}
+	}
+
+	/// Return the character position between two neighbouring world cells, if the layout has one.
+	fn connector_position(&self, coord: Coord, dir: Direction) -> Option<(usize, usize)> {
+		if self.layout != TerminalLayout::Lattice {
+			return None;
+		}
+
+		let next = coord.go_towards(dir);
+		Some((
+			(coord.x + next.x + 1) as usize,
+			(coord.y + next.y + 1) as usize,
+		))
+	}
+
+	fn fill_cell(&mut self, coord: Coord, f: impl Fn(&mut TerminalCell)) {
+		for (x, y) in self.cell_positions(coord) {
+			f(self.cell_mut(x, y));
+		}
+	}
+
+	fn fill_connector(&mut self, coord: Coord, dir: Direction, f: impl Fn(&mut TerminalCell)) {
+		if let Some((x, y)) = self.connector_position(coord, dir) {
+			f(self.cell_mut(x, y));
+		}
+	}
+
+	/// Draw a wall line between two neighbouring lattice corners. Only used in the lattice layout.
+	fn draw_wall(&mut self, corner: Coord, dir: Direction, color: Color32) {
+		let stride = self.corner_stride;
+		let next = corner.go_towards(dir);
+
+		let (arm, opposite_arm) = match dir {
+			Direction::Up => (ARM_UP, ARM_DOWN),
+			Direction::Down => (ARM_DOWN, ARM_UP),
+			Direction::Left => (ARM_LEFT, ARM_RIGHT),
+			Direction::Right => (ARM_RIGHT, ARM_LEFT),
+		};
+
+		self.corner_arms[corner.y as usize * stride + corner.x as usize] |= arm;
+		self.corner_arms[next.y as usize * stride + next.x as usize] |= opposite_arm;
+
+		let ch = match dir {
+			Direction::Up | Direction::Down => '│',
+			Direction::Left | Direction::Right => '─',
+		};
+
+		let (x, y) = ((corner.x + next.x) as usize, (corner.y + next.y) as usize);
+		*self.cell_mut(x, y) = TerminalCell {
+			ch,
+			fg: Some(color),
+			bg: None,
+		};
+
+		for corner in [corner, next] {
+			let cell = self.cell_mut(corner.x as usize * 2, corner.y as usize * 2);
+			cell.fg = Some(color);
+		}
+	}
+
+	/// Resolve the corner characters from the accumulated wall arms.
+	fn finish_corners(&mut self) {
+		if self.layout != TerminalLayout::Lattice {
+			return;
+		}
+
+		let stride = self.corner_stride;
+		for y in 0..stride {
+			for x in 0..stride {
+				let arms = self.corner_arms[y * stride + x];
+				if arms == 0 {
+					continue;
+				}
+
+				self.cell_mut(x * 2, y * 2).ch = box_drawing_char(arms);
+			}
+		}
+	}
+
+	fn into_string(self) -> String {
+		let mut output = String::with_capacity(self.cells.len() * 2);
+
+		for row in self.cells.chunks(self.width) {
+			let mut current_style = (None, None);
+
+			for cell in row {
+				if self.colors == TerminalColors::TrueColor && (cell.fg, cell.bg) != current_style {
+					output.push_str("\x1b[0m");
+					if let Some(fg) = cell.fg {
+						write!(output, "\x1b[38;2;{};{};{}m", fg.r(), fg.g(), fg.b()).unwrap();
+					}
+					if let Some(bg) = cell.bg {
+						write!(output, "\x1b[48;2;{};{};{}m", bg.r(), bg.g(), bg.b()).unwrap();
+					}
+					current_style = (cell.fg, cell.bg);
+				}
+
+				output.push(cell.ch);
+			}
+
+			if self.colors == TerminalColors::TrueColor {
+				output.push_str("\x1b[0m");
+			}
+			output.push('\n');
+		}
+
+		output
+	}
+}
+
+fn box_drawing_char(arms: u8) -> char {
+	let up = arms & ARM_UP != 0;
+	let down = arms & ARM_DOWN != 0;
+	let left = arms & ARM_LEFT != 0;
+	let right = arms & ARM_RIGHT != 0;
+
+	match (up, down, left, right) {
+		(_, _, false, false) => '│',
+		(false, false, _, _) => '─',
+		(false, true, false, true) => '┌',
+		(false, true, true, false) => '┐',
+		(true, false, false, true) => '└',
+		(true, false, true, false) => '┘',
+		(true, true, false, true) => '├',
+		(true, true, true, false) => '┤',
+		(false, true, true, true) => '┬',
+		(true, false, true, true) => '┴',
+		(true, true, true, true) => '┼',
+	}
+}
+
+const FOOD_COLOR: Color32 = Color32::from_rgb(255, 0, 0);
+const BODY_COLOR: Color32 = Color32::from_rgb(0, 255, 0);
+const HEAD_COLOR: Color32 = Color32::from_rgb(0, 128, 0);
+const PATH_COLOR: Color32 = Color32::from_rgb(255, 255, 0);
+
+impl SnakeWorldViewer<'_> {
+	/// Render the world and all of its overlays into a string that can be printed to a terminal.
+	///
+	/// Worlds without edge grid overlays are drawn two characters per cell. When edge grid overlays
+	/// are present, a lattice layout is used instead so that walls can be drawn between cells.
+	pub fn render_terminal(&self, colors: TerminalColors) -> String {
+		let layout = if self.bools_edges_grid.is_empty() {
+			TerminalLayout::Compact
+		} else {
+			TerminalLayout::Lattice
+		};
+
+		let plain = colors == TerminalColors::Plain;
+		let mut canvas = TerminalCanvas::new(self.snake_world.size(), layout, colors);
+
+		// Add the empty cell markers for plain output, as there's no background to show the board
+		if plain {
+			let size = self.snake_world.size();
+			for coord in (0..size).flat_map(|x| (0..size).map(move |y| Coord::new(x, y))) {
+				let positions = canvas.cell_positions(coord);
+				let (x, y) = positions[positions.len() - 1];
+				canvas.cell_mut(x, y).ch = '.';
+			}
+		}
+
+		if let (Some(pathfinding), false) = (&self.pathfinding_grid, plain) {
+			// Find the maximum value
+			let max_value = pathfinding
+				.iter_all_coords()
+				.map(|coord| *pathfinding.get(coord).unwrap())
+				.max()
+				.unwrap_or(0)
+				.max(1);
+
+			for coord in pathfinding.iter_all_coords() {
+				let value = *pathfinding.get(coord).unwrap();
+				let color =
+					Color32::from_rgb((255.0 * (value as f32 / max_value as f32)) as u8, 0, 0);
+				canvas.fill_cell(coord, |cell| cell.bg = Some(color));
+			}
+		}
+
+		// Add food
+		let (food_char, food_char_compact) = if plain {
+			('*', "<>")
+		} else {
+			('●', "██")
+		};
+		draw_cell_glyph(
+			&mut canvas,
+			self.snake_world.food_coord(),
+			food_char,
+			food_char_compact,
+			FOOD_COLOR,
+		);
+
+		// Add the snake body, joining neighbouring cells together in the lattice layout
+		let (body_char, body_char_compact) = if plain {
+			('o', "[]")
+		} else {
+			('█', "██")
+		};
+		let head = self.snake_world.snake_head_coord();
+		let mut current = head;
+		for dir in self
+			.snake_world
+			.calculate_snake_path_from_head()
+			.iter_directions()
+		{
+			canvas.fill_connector(current, dir, |cell| {
+				cell.ch = body_char;
+				cell.fg = Some(BODY_COLOR);
+			});
+			current = current.go_towards(dir);
+			draw_cell_glyph(
+				&mut canvas,
+				current,
+				body_char,
+				body_char_compact,
+				BODY_COLOR,
+			);
+		}
+
+		let (head_char, head_char_compact) = if plain {
+			('@', "@@")
+		} else {
+			('█', "██")
+		};
+		draw_cell_glyph(&mut canvas, head, head_char, head_char_compact, HEAD_COLOR);
+
+		// Add the path overlay, only drawing over cells that are otherwise empty
+		if let Some(path) = &self.overlay_path {
+			let mut current = head;
+			for dir in path.iter_directions() {
+				canvas.fill_connector(current, dir, |cell| draw_path_char(cell, plain));
+				current = current.go_towards(dir);
+				canvas.fill_cell(current, |cell| draw_path_char(cell, plain));
+			}
+		}
+
+		// Add the edge grid walls, which only exist in the lattice layout
+		for (bools_edges_grid, color) in self.bools_edges_grid.iter() {
+			for coord in bools_edges_grid.iter_all_coords() {
+				for dir in [Direction::Right, Direction::Down].into_iter() {
+					if bools_edges_grid.get_edge(coord, dir) == Some(&true) {
+						let start = coord.go_towards(dir);
+
+						let next_dir = match dir {
+							Direction::Right => dir.rotate_right(),
+							Direction::Down => dir.rotate_left(),
+							_ => unreachable!(),
+						};
+
+						canvas.draw_wall(start, next_dir, *color);
+					}
+				}
+			}
+		}
+
+		canvas.finish_corners();
+		canvas.into_string()
+	}
+}
+
+fn draw_cell_glyph(
+	canvas: &mut TerminalCanvas,
+	coord: Coord,
+	lattice_char: char,
+	compact_chars: &str,
+	color: Color32,
+) {
+	let positions = canvas.cell_positions(coord);
+	let chars: Vec<char> = match canvas.layout {
+		TerminalLayout::Compact => compact_chars.chars().collect(),
+		TerminalLayout::Lattice => vec![lattice_char],
+	};
+
+	for ((x, y), ch) in positions.into_iter().zip(chars) {
+		let cell = canvas.cell_mut(x, y);
+		cell.ch = ch;
+		cell.fg = Some(color);
+	}
+}
+
+fn draw_path_char(cell: &mut TerminalCell, plain: bool) {
+	if cell.ch == ' ' || (plain && cell.ch == '.') {
+		cell.ch = if plain { '+' } else { '·' };
+		cell.fg = Some(PATH_COLOR);
+	}
+}
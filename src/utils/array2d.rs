@@ -11,7 +11,7 @@ pub struct Array2D<T> {
 impl<T> Array2D<T> {
 	fn coord_to_index(&self, coord: Coord) -> Option<usize> {
 		if !self.is_in_bounds(coord) {
-			return None;
+			None
 		} else {
 			Some(coord.y as usize * self.size + coord.x as usize)
 		}
@@ -1,5 +1,6 @@
 use crate::{array2d::Array2D, direction::Direction, Coord};
 
+#[derive(Clone)]
 pub struct GridGraph<T> {
 	size: usize,
 	cells: Array2D<T>,
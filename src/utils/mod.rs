@@ -2,3 +2,4 @@ pub mod array2d;
 pub mod direction;
 pub mod grid_graph;
 pub mod path;
+pub mod rng;
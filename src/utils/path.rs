@@ -7,6 +7,12 @@ pub struct Path {
 	directions: VecDeque<Direction>,
 }
 
+impl Default for Path {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl Path {
 	pub fn new() -> Self {
 		Self {
@@ -26,6 +32,10 @@ impl Path {
 		self.directions.is_empty()
 	}
 
+	pub fn len(&self) -> usize {
+		self.directions.len()
+	}
+
 	pub fn iter_offsets(&self) -> impl '_ + Iterator<Item = Offset> {
 		let first = std::iter::once(Offset::zero());
 
@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+thread_local! {
+	static THREAD_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseed the current thread's random number generator. Every random decision made by the
+/// snake world and the solvers on this thread is drawn from it, so a game that is played
+/// entirely on one thread after seeding is reproducible.
+pub fn seed_thread_rng(seed: u64) {
+	THREAD_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Get a handle to the current thread's seedable random number generator.
+pub fn thread_rng() -> SnakeRng {
+	SnakeRng { _private: () }
+}
+
+/// A handle to the thread local random number generator, see `seed_thread_rng`.
+#[derive(Debug, Clone, Copy)]
+pub struct SnakeRng {
+	_private: (),
+}
+
+impl RngCore for SnakeRng {
+	fn next_u32(&mut self) -> u32 {
+		THREAD_RNG.with(|rng| rng.borrow_mut().next_u32())
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		THREAD_RNG.with(|rng| rng.borrow_mut().next_u64())
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		THREAD_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+		THREAD_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+	}
+}
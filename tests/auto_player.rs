@@ -0,0 +1,112 @@
+//! Checks the auto player's step limits and observer hooks.
+
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use snake_solver::{
+	auto::{AutoPlayerState, AutoSnakePlayer, GameStats, Path, PlayerObserver},
+	direction::Direction,
+	rng::seed_thread_rng,
+	snake::{SnakeResult, SnakeWorld},
+	solvers::{
+		random_spanning_tree::RandomSpanningTreeSolver,
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+	},
+};
+
+#[test]
+fn auto_player_stalls_when_out_of_steps() {
+	seed_thread_rng(0);
+	let mut player = AutoSnakePlayer::new(10, SnakeSpanningTreeSolver::new(JitterKind::NoJitter))
+		.with_max_steps(10);
+	while player.state() == AutoPlayerState::Playing {
+		player.step();
+	}
+
+	assert_eq!(player.state(), AutoPlayerState::Stalled);
+	assert_eq!(player.stats().total_steps, 10);
+	assert_eq!(player.step(), SnakeResult::Stepped);
+	assert_eq!(player.state(), AutoPlayerState::Stalled);
+	assert_eq!(player.stats().total_steps, 10);
+
+	seed_thread_rng(0);
+	let mut player =
+		AutoSnakePlayer::new(10, RandomSpanningTreeSolver::new()).with_max_steps_without_food(3);
+	while player.state() == AutoPlayerState::Playing {
+		player.step();
+	}
+
+	// The snake stalls on its third step after the last food it ate
+	let stats = player.stats();
+	assert_eq!(player.state(), AutoPlayerState::Stalled);
+	assert_eq!(
+		stats.total_steps - stats.steps_per_food.iter().sum::<u64>(),
+		3
+	);
+}
+
+#[derive(Default)]
+struct EventCounts {
+	paths: u64,
+	path_lengths: Vec<usize>,
+	steps: u64,
+	steps_per_food: Vec<u64>,
+	ended: Vec<AutoPlayerState>,
+}
+
+struct CountingObserver(Arc<Mutex<EventCounts>>);
+
+impl PlayerObserver for CountingObserver {
+	fn on_path_planned(&mut self, _world: &SnakeWorld, path: &Path, _elapsed: Duration) {
+		let mut counts = self.0.lock().unwrap();
+		counts.paths += 1;
+		counts.path_lengths.push(path.len());
+	}
+
+	fn on_step(&mut self, _world: &SnakeWorld, _direction: Direction) {
+		self.0.lock().unwrap().steps += 1;
+	}
+
+	fn on_food_eaten(&mut self, _world: &SnakeWorld, steps: u64) {
+		self.0.lock().unwrap().steps_per_food.push(steps);
+	}
+
+	fn on_game_ended(&mut self, _world: &SnakeWorld, state: AutoPlayerState, _stats: &GameStats) {
+		self.0.lock().unwrap().ended.push(state);
+	}
+}
+
+#[test]
+fn auto_player_observers_see_every_event() {
+	let counts = Arc::new(Mutex::new(EventCounts::default()));
+
+	seed_thread_rng(0);
+	let player = AutoSnakePlayer::new(
+		10,
+		SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10)),
+	)
+	.with_observer(CountingObserver(counts.clone()));
+
+	// Players with observers can be moved to another thread to be played
+	let player = std::thread::spawn(move || {
+		let mut player = player;
+		seed_thread_rng(1);
+		while player.state() == AutoPlayerState::Playing {
+			player.step();
+		}
+		player.step();
+		player
+	})
+	.join()
+	.unwrap();
+
+	let counts = counts.lock().unwrap();
+	let stats = player.stats();
+	assert_eq!(counts.paths, stats.path_computations);
+	assert_eq!(counts.path_lengths, stats.path_lengths);
+	assert_eq!(counts.steps, stats.total_steps);
+	assert_eq!(counts.steps_per_food, stats.steps_per_food);
+	assert_eq!(counts.ended, [AutoPlayerState::Finished]);
+}
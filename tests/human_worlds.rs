@@ -0,0 +1,67 @@
+//! Feeds the dynamic solver worlds shaped by a human-like player instead of by the solver itself.
+//! A human's snake doesn't follow the spanning tree's stepping rules, so the solver can fail to
+//! find a path, but it should always return one without hanging or running out of memory.
+
+use rand::Rng;
+use snake_solver::{
+	direction::Direction,
+	rng::{seed_thread_rng, thread_rng},
+	snake::{SnakeResult, SnakeWorld},
+	solvers::{
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+		SnakeSolver,
+	},
+};
+
+const SIZE: usize = 12;
+const SEEDS: u64 = 20;
+const STEPS: usize = 200;
+
+/// Keep going straight most of the time, and turn at random otherwise, without dying if possible.
+fn human_move(world: &SnakeWorld) -> Option<Direction> {
+	let safe = Direction::each()
+		.filter(|&dir| world.clone().step_snake(dir) == SnakeResult::Stepped)
+		.collect::<Vec<_>>();
+
+	let heading = world.prev_direction().filter(|dir| safe.contains(dir));
+	match heading {
+		Some(dir) if thread_rng().gen_bool(0.7) => Some(dir),
+		_ if safe.is_empty() => None,
+		_ => Some(safe[thread_rng().gen_range(0..safe.len())]),
+	}
+}
+
+fn check_solver(make_solver: impl Fn() -> SnakeSpanningTreeSolver) {
+	// Seed 12 used to send the solver's pathfinding around in a loop forever
+	for seed in (0..SEEDS).chain([12]) {
+		seed_thread_rng(seed);
+		let mut world = SnakeWorld::new(SIZE);
+		let mut solver = make_solver();
+
+		for _ in 0..STEPS {
+			let Some(dir) = human_move(&world) else {
+				break;
+			};
+			if world.step_snake(dir) != SnakeResult::Stepped {
+				break;
+			}
+
+			let path = solver.get_next_path(&world);
+			assert!(!path.is_empty(), "seed {}", seed);
+		}
+	}
+}
+
+#[test]
+fn dynamic_solver_plans_for_human_worlds() {
+	check_solver(|| SnakeSpanningTreeSolver::new(JitterKind::NoJitter));
+}
+
+#[test]
+fn incremental_dynamic_solver_plans_for_human_worlds() {
+	check_solver(|| {
+		SnakeSpanningTreeSolver::new(JitterKind::NoJitter)
+			.with_incremental(true)
+			.with_detour_removal(true)
+	});
+}
@@ -0,0 +1,46 @@
+//! Checks the occupancy forecast against the snake as it follows the forecast path.
+
+use snake_solver::{
+	rng::seed_thread_rng,
+	snake::{Cell, SnakeResult, SnakeWorld},
+	solvers::{
+		snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+		SnakeSolver,
+	},
+};
+
+#[test]
+fn occupancy_forecast_matches_the_snake() {
+	for seed in 0..4 {
+		seed_thread_rng(seed);
+		let mut world = SnakeWorld::new(10);
+		let mut solver = SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10));
+
+		'game: loop {
+			let path = solver.get_next_path(&world);
+			let forecast = world.forecast_occupancy(&path);
+			assert!(forecast.is_path_safe());
+
+			let grid = forecast.free_after_grid();
+			let mut entered = grid
+				.iter_all_coords()
+				.filter(|&coord| matches!(world.get_cell(coord), Some(Cell::Snake(_))))
+				.collect::<Vec<_>>();
+
+			for (step, dir) in path.iter_directions().enumerate() {
+				match world.step_snake(dir) {
+					SnakeResult::Stepped => {}
+					SnakeResult::Finished => break 'game,
+					SnakeResult::Killed => panic!("Snake was killed"),
+				}
+				entered.push(world.snake_head_coord());
+
+				// Cells the snake has reached should stay covered until the forecast says they're free
+				for &coord in entered.iter() {
+					let covered = matches!(world.get_cell(coord), Some(Cell::Snake(_)));
+					assert_eq!(covered, grid[coord] > step as u32 + 1, "{:?}", coord);
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,55 @@
+//! Checks that batches of simulated games play out the same as games run one at a time.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use snake_solver::{
+	auto::{
+		simulate_many, simulate_many_with_progress, AutoPlayerState, AutoSnakePlayer,
+		SimulationConfig,
+	},
+	rng::seed_thread_rng,
+	solvers::snake_spanning_tree::{JitterKind, SnakeSpanningTreeSolver},
+};
+
+#[test]
+fn simulate_many_matches_single_games() {
+	let make_solver = || SnakeSpanningTreeSolver::new(JitterKind::JitterWhenIndirect(10));
+	let results = simulate_many(SimulationConfig::new(10), 0..8, make_solver);
+
+	assert_eq!(results.finished_count(), 8);
+	for (seed, game) in (0..8).zip(results.games.iter()) {
+		// Each game should play out exactly as it does when run on its own
+		seed_thread_rng(seed);
+		let mut player = AutoSnakePlayer::new(10, make_solver());
+		while player.state() == AutoPlayerState::Playing {
+			player.step();
+		}
+
+		assert_eq!(game.seed, seed);
+		assert_eq!(game.state, player.state());
+		assert_eq!(game.stats.total_steps, player.stats().total_steps);
+		assert_eq!(game.stats.path_lengths, player.stats().path_lengths);
+	}
+}
+
+#[test]
+fn simulate_many_reports_every_game() {
+	let make_solver = || SnakeSpanningTreeSolver::new(JitterKind::NoJitter);
+	let played = AtomicUsize::new(0);
+	let results = simulate_many_with_progress(
+		SimulationConfig::new(6).with_threads(2),
+		0..6,
+		make_solver,
+		|_| {
+			played.fetch_add(1, Ordering::Relaxed);
+		},
+	);
+
+	assert_eq!(played.into_inner(), 6);
+	let seeds = results
+		.games
+		.iter()
+		.map(|game| game.seed)
+		.collect::<Vec<_>>();
+	assert_eq!(seeds, [0, 1, 2, 3, 4, 5]);
+}
@@ -0,0 +1,402 @@
+//! Runs every solver over many seeds and world sizes, checking that each game finishes without
+//! the snake dying. Failures are shrunk to the smallest world size and seed that still fail,
+//! and a snapshot of the failing world is written next to the test binaries.
+//!
+//! The number of seeds per size can be raised with the `SNAKE_FUZZ_SEEDS` environment variable.
+
+use std::{
+	panic::{catch_unwind, AssertUnwindSafe},
+	time::Duration,
+};
+
+use snake_solver::{
+	auto::{AutoPlayerState, AutoSnakePlayer, Path},
+	rng::seed_thread_rng,
+	snake::SnakeWorld,
+	solvers::{
+		basic::BasicSnakeSolver,
+		random_spanning_tree::RandomSpanningTreeSolver,
+		snake_spanning_tree::{
+			pathfinding::StepCosts, JitterKind, SnakeSpanningTreeSolver, TreeGrowthStrategy,
+		},
+		validating::ValidatingSolver,
+		SnakeSolver,
+	},
+	ui::{SnakeWorldViewer, TerminalColors},
+};
+
+const SIZES: [usize; 6] = [4, 6, 8, 10, 12, 14];
+const DEFAULT_SEEDS: u64 = 10;
+
+#[derive(Debug, Copy, Clone)]
+enum SolverConfig {
+	ZigZag,
+	StaticHamiltonian,
+	RegeneratingStaticHamiltonian,
+	DynamicHamiltonian(JitterKind),
+	IncrementalDynamicHamiltonian(JitterKind),
+	GrownDynamicHamiltonian(TreeGrowthStrategy),
+	WeightedDynamicHamiltonian(StepCosts, bool),
+	CycleCheckedStaticHamiltonian,
+	CycleCheckedDynamicHamiltonian(TreeGrowthStrategy),
+	CycleCheckedDetourFreeDynamicHamiltonian {
+		incremental: bool,
+	},
+	EndgameDynamicHamiltonian {
+		jitter: JitterKind,
+		incremental: bool,
+	},
+}
+
+impl SolverConfig {
+	fn run(&self, size: usize, seed: u64) -> Result<(), String> {
+		match *self {
+			SolverConfig::ZigZag => run_game(ValidatingSolver::new(BasicSnakeSolver), size, seed),
+			SolverConfig::StaticHamiltonian => run_game(
+				ValidatingSolver::new(RandomSpanningTreeSolver::new()),
+				size,
+				seed,
+			),
+			SolverConfig::RegeneratingStaticHamiltonian => run_game(
+				ValidatingSolver::new(
+					RandomSpanningTreeSolver::new()
+						.with_food_bias(0.5)
+						.with_regeneration(true),
+				),
+				size,
+				seed,
+			),
+			SolverConfig::DynamicHamiltonian(jitter) => {
+				run_game(dynamic_solver(jitter, false), size, seed)
+			}
+			SolverConfig::IncrementalDynamicHamiltonian(jitter) => {
+				run_game(dynamic_solver(jitter, true), size, seed)
+			}
+			SolverConfig::GrownDynamicHamiltonian(growth) => run_game(
+				ValidatingSolver::new(
+					SnakeSpanningTreeSolver::new(JitterKind::NoJitter).with_growth_strategy(growth),
+				),
+				size,
+				seed,
+			),
+			SolverConfig::WeightedDynamicHamiltonian(step_costs, incremental) => run_game(
+				ValidatingSolver::new(
+					SnakeSpanningTreeSolver::new(JitterKind::NoJitter)
+						.with_step_costs(step_costs)
+						.with_incremental(incremental),
+				),
+				size,
+				seed,
+			),
+			SolverConfig::CycleCheckedStaticHamiltonian => run_game(
+				ValidatingSolver::new(CycleCheckingSolver(RandomSpanningTreeSolver::new())),
+				size,
+				seed,
+			),
+			SolverConfig::CycleCheckedDetourFreeDynamicHamiltonian { incremental } => run_game(
+				ValidatingSolver::new(CycleCheckingSolver(
+					SnakeSpanningTreeSolver::new(JitterKind::NoJitter)
+						.with_incremental(incremental)
+						.with_detour_removal(true),
+				)),
+				size,
+				seed,
+			),
+			SolverConfig::EndgameDynamicHamiltonian {
+				jitter,
+				incremental,
+			} => run_game(
+				ValidatingSolver::new(CycleCheckingSolver(
+					SnakeSpanningTreeSolver::new(jitter)
+						.with_incremental(incremental)
+						.with_endgame(50),
+				))
+				.allow_partial_paths(),
+				size,
+				seed,
+			),
+			SolverConfig::CycleCheckedDynamicHamiltonian(growth) => run_game(
+				ValidatingSolver::new(CycleCheckingSolver(
+					SnakeSpanningTreeSolver::new(JitterKind::NoJitter).with_growth_strategy(growth),
+				)),
+				size,
+				seed,
+			),
+		}
+	}
+}
+
+/// Checks that the cycle behind every path visits each cell exactly once, and that the path
+/// follows it from the head.
+struct CycleCheckingSolver<S: SnakeSolver>(S);
+
+impl<S: SnakeSolver> SnakeSolver for CycleCheckingSolver<S> {
+	fn get_next_path(&mut self, world: &SnakeWorld) -> Path {
+		let path = self.0.get_next_path(world);
+
+		let cycle = self
+			.0
+			.hamiltonian_cycle(world)
+			.expect("Solver has no cycle");
+		if let Err(violation) = cycle.verify() {
+			panic!("{}", violation);
+		}
+
+		let mut current = world.snake_head_coord();
+		for dir in path.iter_directions() {
+			let next = current.go_towards(dir);
+			assert_eq!(cycle.next_coord(current), Some(next), "Path left the cycle");
+			current = next;
+		}
+
+		path
+	}
+
+	fn decorate_widget<'a>(&'a self, widget: SnakeWorldViewer<'a>) -> SnakeWorldViewer<'a> {
+		self.0.decorate_widget(widget)
+	}
+}
+
+fn dynamic_solver(
+	jitter: JitterKind,
+	incremental: bool,
+) -> ValidatingSolver<SnakeSpanningTreeSolver> {
+	let solver =
+		ValidatingSolver::new(SnakeSpanningTreeSolver::new(jitter).with_incremental(incremental));
+
+	match jitter {
+		JitterKind::NoJitter => solver,
+		_ => solver.allow_partial_paths(),
+	}
+}
+
+/// Play a seeded game to the end, returning a description of the failure if the snake
+/// died, an invalid path was returned, the solver panicked or the game didn't finish in time.
+fn run_game<S: SnakeSolver>(
+	solver: ValidatingSolver<S>,
+	size: usize,
+	seed: u64,
+) -> Result<(), String> {
+	let max_steps = 2 * (size as u64).pow(4);
+
+	let result = catch_unwind(AssertUnwindSafe(|| {
+		seed_thread_rng(seed);
+		let mut player = AutoSnakePlayer::new(size, solver).with_max_steps(max_steps);
+
+		while player.state() == AutoPlayerState::Playing {
+			player.step();
+		}
+
+		if player.state() == AutoPlayerState::Stalled {
+			return Err(format!(
+				"Didn't finish within {} steps\n{}",
+				max_steps,
+				SnakeWorldViewer::new(player.world()).render_terminal(TerminalColors::Plain)
+			));
+		}
+
+		if player.state() == AutoPlayerState::Killed {
+			return Err(format!(
+				"Snake was killed\n{}",
+				SnakeWorldViewer::new(player.world())
+					.with_path_overlay(player.current_path())
+					.render_terminal(TerminalColors::Plain)
+			));
+		}
+
+		Ok(())
+	}));
+
+	match result {
+		Ok(result) => result,
+		Err(panic) => {
+			let message = panic
+				.downcast_ref::<String>()
+				.cloned()
+				.or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+				.unwrap_or_else(|| "Unknown panic".to_string());
+			Err(format!("Panicked: {}", message))
+		}
+	}
+}
+
+/// Find the smallest world size, and then the smallest seed at that size, that still fails.
+fn shrink(config: SolverConfig, size: usize, seed: u64, failure: String) -> (usize, u64, String) {
+	for &smaller_size in SIZES.iter().filter(|&&s| s <= size) {
+		for smaller_seed in 0..=seed {
+			if smaller_size == size && smaller_seed == seed {
+				return (size, seed, failure);
+			}
+
+			if let Err(failure) = config.run(smaller_size, smaller_seed) {
+				return (smaller_size, smaller_seed, failure);
+			}
+		}
+	}
+
+	(size, seed, failure)
+}
+
+fn check_solver(config: SolverConfig) {
+	let seeds = std::env::var("SNAKE_FUZZ_SEEDS")
+		.ok()
+		.and_then(|seeds| seeds.parse::<u64>().ok())
+		.unwrap_or(DEFAULT_SEEDS);
+
+	for seed in 0..seeds {
+		for &size in SIZES.iter() {
+			let Err(failure) = config.run(size, seed) else {
+				continue;
+			};
+
+			let (size, seed, failure) = shrink(config, size, seed, failure);
+			let report = format!(
+				"{:?} failed on world size {} with seed {}\n{}",
+				config, size, seed, failure
+			);
+
+			let dump_path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"))
+				.join(format!("fuzz-failure-{:?}-{}-{}.txt", config, size, seed));
+			let _ = std::fs::write(&dump_path, &report);
+
+			panic!("{}\nSnapshot written to {}", report, dump_path.display());
+		}
+	}
+}
+
+#[test]
+fn zigzag_always_finishes() {
+	check_solver(SolverConfig::ZigZag);
+}
+
+#[test]
+fn static_hamiltonian_always_finishes() {
+	check_solver(SolverConfig::StaticHamiltonian);
+}
+
+#[test]
+fn regenerating_static_hamiltonian_always_finishes() {
+	check_solver(SolverConfig::RegeneratingStaticHamiltonian);
+}
+
+#[test]
+fn dynamic_hamiltonian_always_finishes() {
+	check_solver(SolverConfig::DynamicHamiltonian(JitterKind::NoJitter));
+}
+
+#[test]
+fn dynamic_hamiltonian_jitter_when_indirect_always_finishes() {
+	check_solver(SolverConfig::DynamicHamiltonian(
+		JitterKind::JitterWhenIndirect(10),
+	));
+	check_solver(SolverConfig::DynamicHamiltonian(
+		JitterKind::JitterWhenIndirect(1),
+	));
+}
+
+#[test]
+fn dynamic_hamiltonian_jitter_always_always_finishes() {
+	check_solver(SolverConfig::DynamicHamiltonian(JitterKind::JitterAlways(
+		1,
+	)));
+}
+
+#[test]
+fn dynamic_hamiltonian_adaptive_jitter_always_finishes() {
+	check_solver(SolverConfig::DynamicHamiltonian(
+		JitterKind::JitterWhenLonger {
+			percent: 20,
+			steps: 1,
+		},
+	));
+	check_solver(SolverConfig::DynamicHamiltonian(
+		JitterKind::JitterBelowFill {
+			percent: 50,
+			steps: 1,
+		},
+	));
+	check_solver(SolverConfig::DynamicHamiltonian(
+		JitterKind::JitterWithinBudget {
+			budget: Duration::from_millis(5),
+			steps: 1,
+		},
+	));
+}
+
+#[test]
+fn incremental_dynamic_hamiltonian_always_finishes() {
+	check_solver(SolverConfig::IncrementalDynamicHamiltonian(
+		JitterKind::NoJitter,
+	));
+	check_solver(SolverConfig::IncrementalDynamicHamiltonian(
+		JitterKind::JitterWhenIndirect(10),
+	));
+	check_solver(SolverConfig::IncrementalDynamicHamiltonian(
+		JitterKind::JitterAlways(1),
+	));
+}
+
+#[test]
+fn dynamic_hamiltonian_growth_strategies_always_finish() {
+	for growth in [
+		TreeGrowthStrategy::RandomizedPrim,
+		TreeGrowthStrategy::Wilson,
+		TreeGrowthStrategy::Compact,
+	] {
+		check_solver(SolverConfig::GrownDynamicHamiltonian(growth));
+	}
+}
+
+#[test]
+fn weighted_dynamic_hamiltonian_always_finishes() {
+	for step_costs in [
+		StepCosts {
+			clockwise: 1,
+			out: 3,
+			turn: 0,
+		},
+		StepCosts {
+			clockwise: 2,
+			out: 1,
+			turn: 1,
+		},
+	] {
+		check_solver(SolverConfig::WeightedDynamicHamiltonian(step_costs, false));
+		check_solver(SolverConfig::WeightedDynamicHamiltonian(step_costs, true));
+	}
+}
+
+#[test]
+fn spanning_tree_solvers_follow_hamiltonian_cycles() {
+	check_solver(SolverConfig::CycleCheckedStaticHamiltonian);
+	for growth in [
+		TreeGrowthStrategy::RandomDfs,
+		TreeGrowthStrategy::RandomizedPrim,
+		TreeGrowthStrategy::Wilson,
+		TreeGrowthStrategy::Compact,
+	] {
+		check_solver(SolverConfig::CycleCheckedDynamicHamiltonian(growth));
+	}
+}
+
+#[test]
+fn detour_free_dynamic_hamiltonian_follows_hamiltonian_cycles() {
+	check_solver(SolverConfig::CycleCheckedDetourFreeDynamicHamiltonian { incremental: false });
+	check_solver(SolverConfig::CycleCheckedDetourFreeDynamicHamiltonian { incremental: true });
+}
+
+#[test]
+fn endgame_dynamic_hamiltonian_always_finishes() {
+	check_solver(SolverConfig::EndgameDynamicHamiltonian {
+		jitter: JitterKind::NoJitter,
+		incremental: false,
+	});
+	check_solver(SolverConfig::EndgameDynamicHamiltonian {
+		jitter: JitterKind::JitterAlways(1),
+		incremental: false,
+	});
+	check_solver(SolverConfig::EndgameDynamicHamiltonian {
+		jitter: JitterKind::NoJitter,
+		incremental: true,
+	});
+}
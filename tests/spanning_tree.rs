@@ -0,0 +1,258 @@
+//! Checks the individual phases of building the dynamic solver's spanning tree.
+
+use std::sync::{Arc, Mutex};
+
+use snake_solver::{
+	direction::Direction,
+	rng::seed_thread_rng,
+	snake::{SnakeResult, SnakeWorld},
+	solvers::{
+		snake_spanning_tree::{
+			build_spanning_tree, build_spanning_tree_with_phases,
+			pathfinding::{PathfindingGrid, StepCosts},
+			spanning_tree::SpanningTree,
+			BuildPhase, JitterKind, SnakeSpanningTreeSolver, TreeGrowthStrategy,
+		},
+		SnakeSolver,
+	},
+};
+
+/// What a phase left in the tree and the grid.
+struct PhaseState {
+	phase: BuildPhase,
+	distances: Vec<u32>,
+	non_free_edges: usize,
+	taken_nodes: usize,
+}
+
+impl PhaseState {
+	fn new(phase: BuildPhase, tree: &SpanningTree, grid: &PathfindingGrid) -> Self {
+		let edges = tree.edges();
+		Self {
+			phase,
+			distances: grid
+				.distances()
+				.iter_all_coords()
+				.map(|coord| grid.distances()[coord])
+				.collect(),
+			non_free_edges: edges
+				.iter_all_coords()
+				.flat_map(|coord| {
+					Direction::each().filter_map(move |dir| edges.get_edge(coord, dir))
+				})
+				.filter(|edge| !edge.is_free())
+				.count(),
+			taken_nodes: edges
+				.iter_all_coords()
+				.filter(|&coord| tree.is_tree_node_taken(coord))
+				.count(),
+		}
+	}
+}
+
+/// A world part way through a game, so the snake has a body to trace.
+fn world_with_body() -> SnakeWorld {
+	seed_thread_rng(0);
+	let mut world = SnakeWorld::new(10);
+	let mut solver = SnakeSpanningTreeSolver::new(JitterKind::NoJitter);
+	while world.snake_length() < 20 {
+		for dir in solver.get_next_path(&world).iter_directions() {
+			assert_eq!(world.step_snake(dir), SnakeResult::Stepped);
+		}
+	}
+	world
+}
+
+#[test]
+fn spanning_tree_build_reports_every_phase() {
+	let world = world_with_body();
+	let mut tree = SpanningTree::new(10, TreeGrowthStrategy::Compact);
+	let mut grid = PathfindingGrid::new(10);
+
+	let mut states = Vec::new();
+	let grow_result =
+		build_spanning_tree_with_phases(&world, &mut tree, &mut grid, |phase, tree, grid| {
+			states.push(PhaseState::new(phase, tree, grid))
+		});
+	let phased_path = tree.build_snake_path(&world);
+
+	assert!(grow_result.is_some());
+	let phases = states.iter().map(|state| state.phase).collect::<Vec<_>>();
+	assert_eq!(
+		phases,
+		[
+			BuildPhase::TraceSnake,
+			BuildPhase::FillPathfindingGrid,
+			BuildPhase::Pathfind,
+			BuildPhase::GrowTree,
+		]
+	);
+	let [traced, filled, pathfound, grown] = &states[..] else {
+		unreachable!()
+	};
+
+	// Tracing only marks the body's edges, and doesn't touch the grid
+	assert!(traced.non_free_edges > 0);
+	assert!(traced.distances.iter().all(|&distance| distance == 0));
+
+	// The fill starts from the food, and leaves the tree alone
+	let food_index = filled
+		.distances
+		.iter()
+		.zip(grid.distances().iter_all_coords())
+		.find(|(_, coord)| *coord == world.food_coord())
+		.map(|(&distance, _)| distance);
+	assert_eq!(food_index, Some(1));
+	assert_eq!(filled.non_free_edges, traced.non_free_edges);
+
+	// Pathfinding marks the path's edges in the tree, and leaves the grid alone
+	assert!(pathfound.non_free_edges > filled.non_free_edges);
+	assert_eq!(pathfound.distances, filled.distances);
+
+	// Growing takes every node
+	assert!(grown.taken_nodes > pathfound.taken_nodes);
+	assert_eq!(grown.taken_nodes, 25);
+
+	// The compact growth strategy is deterministic, so building again gives the same path
+	assert_eq!(
+		build_spanning_tree(&world, &mut tree, &mut grid),
+		grow_result
+	);
+	assert!(tree
+		.build_snake_path(&world)
+		.iter_directions()
+		.eq(phased_path.iter_directions()));
+}
+
+#[test]
+fn solver_reports_the_phases_of_its_settings() {
+	let world = world_with_body();
+	let phases_with = |solver: SnakeSpanningTreeSolver| {
+		let phases = Arc::new(Mutex::new(Vec::new()));
+		let hook_phases = phases.clone();
+		let mut solver =
+			solver.with_phase_hook(move |phase, _, _| hook_phases.lock().unwrap().push(phase));
+
+		let mut world = world.clone();
+		let mut planned = Vec::new();
+		for _ in 0..2 {
+			for dir in solver.get_next_path(&world).iter_directions() {
+				assert_eq!(world.step_snake(dir), SnakeResult::Stepped);
+			}
+			planned.push(std::mem::take(&mut *phases.lock().unwrap()));
+		}
+		planned
+	};
+
+	let build = [
+		BuildPhase::TraceSnake,
+		BuildPhase::FillPathfindingGrid,
+		BuildPhase::Pathfind,
+		BuildPhase::GrowTree,
+	];
+	assert_eq!(
+		phases_with(SnakeSpanningTreeSolver::new(JitterKind::NoJitter)),
+		[build, build]
+	);
+
+	// Incremental solvers repair the tree they built for the first path
+	let incremental = phases_with(
+		SnakeSpanningTreeSolver::new(JitterKind::NoJitter)
+			.with_incremental(true)
+			.with_detour_removal(true),
+	);
+	assert_eq!(incremental[0], build);
+	assert_eq!(
+		incremental[1],
+		[BuildPhase::RepairTree, BuildPhase::RemoveDetours]
+	);
+
+	// Once the endgame starts, the locked in cycle is followed
+	let endgame = phases_with(SnakeSpanningTreeSolver::new(JitterKind::NoJitter).with_endgame(0));
+	assert_eq!(endgame[0], build);
+	assert_eq!(endgame[1], [BuildPhase::FollowEndgameCycle]);
+}
+
+#[test]
+fn spanning_tree_covers_the_world_when_the_food_is_next_to_a_new_snake() {
+	let mut checked = 0;
+	for seed in 0..200 {
+		seed_thread_rng(seed);
+		let world = SnakeWorld::new(10);
+		let head = world.snake_head_coord();
+		let food = world.food_coord();
+		if head.map_values(|v| v / 2) != food.map_values(|v| v / 2) {
+			continue;
+		}
+
+		// The snake and the path to the food fit inside one node, so the tree has to be started
+		// from a free edge instead of from the snake
+		let mut tree = SpanningTree::new(10, TreeGrowthStrategy::Compact);
+		let mut grid = PathfindingGrid::new(10);
+		assert!(build_spanning_tree(&world, &mut tree, &mut grid).is_some());
+
+		let cycle = tree.hamiltonian_cycle(head);
+		assert_eq!(cycle.verify(), Ok(()), "seed {}", seed);
+		assert_eq!(cycle.len(), 100, "seed {}", seed);
+		checked += 1;
+	}
+
+	assert!(checked > 0, "No seed put the food next to the snake");
+}
+
+#[test]
+fn path_cost_is_never_below_the_shortest_distance() {
+	let costs = [
+		StepCosts::default(),
+		StepCosts {
+			clockwise: 1,
+			out: 3,
+			turn: 0,
+		},
+		StepCosts {
+			clockwise: 2,
+			out: 1,
+			turn: 0,
+		},
+	];
+
+	let mut compared = 0;
+	let mut cheapest = 0;
+	for seed in 0..50 {
+		seed_thread_rng(seed);
+		let mut world = SnakeWorld::new(10);
+		let mut solver = SnakeSpanningTreeSolver::new(JitterKind::NoJitter);
+
+		'game: for _ in 0..20 {
+			let head = world.snake_head_coord();
+			for step_costs in costs {
+				let mut tree = SpanningTree::new(10, TreeGrowthStrategy::default());
+				let mut grid = PathfindingGrid::new(10).with_step_costs(step_costs);
+				let grow_result = build_spanning_tree(&world, &mut tree, &mut grid);
+				let path = tree.build_snake_path(&world);
+
+				if step_costs == StepCosts::default() {
+					assert_eq!(grid.path_cost(head, &path), path.len() as u32);
+				}
+
+				// The shortest distance is the cheapest route, so no path can cost less
+				if grow_result.is_some() {
+					let distance = grid.shortest_distance_from(head).unwrap();
+					let cost = grid.path_cost(head, &path);
+					assert!(cost >= distance, "seed {}, {:?}", seed, step_costs);
+					compared += 1;
+					cheapest += (cost == distance) as usize;
+				}
+			}
+
+			for dir in solver.get_next_path(&world).iter_directions() {
+				if world.step_snake(dir) != SnakeResult::Stepped {
+					break 'game;
+				}
+			}
+		}
+	}
+
+	assert!(compared > 100);
+	assert!(cheapest > 0);
+}
@@ -0,0 +1,66 @@
+//! Checks path validation against the snake as it follows random paths.
+
+use rand::Rng;
+use snake_solver::{
+	auto::Path,
+	direction::Direction,
+	rng::{seed_thread_rng, thread_rng},
+	snake::{SnakeResult, SnakeWorld},
+	solvers::validating::{validate_path, PathViolationKind},
+};
+
+fn random_direction() -> Direction {
+	Direction::each().nth(thread_rng().gen_range(0..4)).unwrap()
+}
+
+#[test]
+fn validate_path_matches_the_snake() {
+	let mut violations = 0;
+	for seed in 0..200 {
+		seed_thread_rng(seed);
+		let mut world = SnakeWorld::new(8);
+
+		// Wander around for a while so the snake has a body to run into
+		for _ in 0..20 {
+			let dir = random_direction();
+			let mut next_world = world.clone();
+			if next_world.step_snake(dir) == SnakeResult::Stepped {
+				world = next_world;
+			}
+		}
+
+		let mut path = Path::new();
+		for _ in 0..thread_rng().gen_range(1..20) {
+			path.push(random_direction());
+		}
+
+		// Follow the path to find the first step that should be rejected
+		let mut followed = world.clone();
+		let mut expected = None;
+		let path_len = path.len();
+		for (index, dir) in path.iter_directions().enumerate() {
+			let head = followed.snake_head_coord().go_towards(dir);
+			let killed = followed.step_snake(dir) == SnakeResult::Killed;
+			if killed {
+				let kind = match world.get_cell(head) {
+					None => PathViolationKind::OutOfBounds,
+					Some(_) => PathViolationKind::BodyCollision,
+				};
+				expected = Some((kind, index));
+				break;
+			}
+			if head == world.food_coord() && index + 1 != path_len {
+				expected = Some((PathViolationKind::EatsFoodEarly, index));
+				break;
+			}
+		}
+
+		let result = validate_path(&world, &path, false)
+			.err()
+			.map(|violation| (violation.kind, violation.step));
+		assert_eq!(result, expected, "seed {}", seed);
+		violations += expected.is_some() as usize;
+	}
+
+	assert!(violations > 0, "No random path was invalid");
+}